@@ -7,6 +7,7 @@ use crate::components::damage::DamageComponent;
 use crate::plugins::animated_sprite::AnimatedSprite;
 use crate::plugins::engine::ActiveGameEngine;
 use crate::plugins::engine::ActivePlayerEntityId;
+use crate::plugins::engine::EngineReplay;
 use crate::plugins::engine::GameEntityComponent;
 use crate::plugins::help_gui::HelpGuiState;
 use crate::plugins::player_inventory::PlayerInventoryState;
@@ -137,6 +138,7 @@ fn input_system(
     mut action_events: EventWriter<NetworkAction>,
     text_inputs: Query<&TextInput>,
     mut commands: Commands,
+    mut engine_replay: ResMut<EngineReplay>,
 ) {
     let engine = &mut active_game_engine.0;
     if text_inputs.is_empty() && keyboard.just_pressed(KeyCode::Enter) {
@@ -200,6 +202,10 @@ fn input_system(
         };
         // register here, will get confirmation with an id change?
         // for now, no
+        engine_replay.record_events(
+            *engine.step_index(),
+            &[(*engine.step_index(), vec![input_event.clone()])].into(),
+        );
         engine.register_event(None, input_event.clone());
         // send the new input to the server
         action_events.write(NetworkAction(Action::RemoteEngineEvent(
@@ -1,5 +1,8 @@
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
 use std::time::Instant;
 
 use bevy::prelude::*;
@@ -10,6 +13,8 @@ use bevy::text::TextLayoutInfo;
 use db::PlayerRecord;
 use game_common::prelude::*;
 use keind::prelude::*;
+use keind_time::GameEngineTime;
+use keind_time::PingSample;
 
 use crate::GameState;
 use crate::NetworkMessage;
@@ -45,6 +50,54 @@ pub struct LoggedInAt(pub Option<Instant>);
 #[derive(Resource, Default)]
 pub struct ActivePlayerState(pub Option<PlayerRecord>);
 
+/// Replay recording of the active engine, enabled by setting
+/// `REPLAY_DIR_ENV`. Verify a recording with `keind_replay`.
+#[derive(Resource, Default)]
+pub struct EngineReplay(pub Option<ReplayWriter<KeindGameLogic, BufWriter<File>>>);
+
+impl EngineReplay {
+    /// Start a new recording from `engine`, replacing any previous one.
+    pub fn start(&mut self, engine: &GameEngine<KeindGameLogic>, game_data: &GameData) {
+        self.0 = None;
+        let Ok(replay_dir) = std::env::var(REPLAY_DIR_ENV) else {
+            return;
+        };
+        let path = Path::new(&replay_dir).join(format!("client-{}.replay", engine.id()));
+        let writer = File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| ReplayWriter::new(BufWriter::new(file), engine, game_data.hash()?));
+        match writer {
+            Ok(writer) => {
+                println!("INFO: recording replay to {:?}", path);
+                self.0 = Some(writer);
+            }
+            Err(e) => println!("WARNING: failed to start replay recording: {e}"),
+        }
+    }
+
+    pub fn record_events(
+        &mut self,
+        integrated_at: u64,
+        events: &BTreeMap<u64, Vec<EngineEvent<KeindGameLogic>>>,
+    ) {
+        if let Some(writer) = &mut self.0
+            && let Err(e) = writer.record_events(integrated_at, events)
+        {
+            println!("WARNING: failed to write replay, stopping recording: {e}");
+            self.0 = None;
+        }
+    }
+
+    pub fn record_step_hash(&mut self, engine: &GameEngine<KeindGameLogic>) {
+        if let Some(writer) = &mut self.0
+            && let Err(e) = writer.record_step_hash(engine)
+        {
+            println!("WARNING: failed to write replay, stopping recording: {e}");
+            self.0 = None;
+        }
+    }
+}
+
 pub struct EnginePlugin;
 
 impl Plugin for EnginePlugin {
@@ -53,6 +106,7 @@ impl Plugin for EnginePlugin {
             .init_resource::<ActivePlayerEntityId>()
             .init_resource::<ActivePlayerState>()
             .init_resource::<LoggedInAt>()
+            .init_resource::<EngineReplay>()
             .init_resource::<InterpolatingEntities>()
             .add_systems(
                 Update,
@@ -60,6 +114,7 @@ impl Plugin for EnginePlugin {
                     handle_engine_state,
                     handle_engine_stats,
                     handle_engine_event,
+                    sync_server_clock,
                     step_game_engine,
                     sync_engine_components,
                     add_simple_bubble_background,
@@ -105,36 +160,63 @@ fn handle_player_state(
     }
 }
 
+/// Seconds between pings to estimate the server clock.
+const PING_INTERVAL_S: f64 = 1.0;
+
+fn sync_server_clock(
+    mut action_events: EventReader<NetworkMessage>,
+    mut action_events_writer: EventWriter<NetworkAction>,
+    mut sync_info: ResMut<EngineSyncInfo>,
+) {
+    let now = GameEngineTime::now();
+    for event in action_events.read() {
+        if let Response::Pong(sent, remote_received, remote_sent) = &event.0 {
+            sync_info.clock_sync.add_sample(PingSample {
+                sent: *sent,
+                remote_received: *remote_received,
+                remote_sent: *remote_sent,
+                received: now,
+            });
+        }
+    }
+    if now - sync_info.last_ping_timestamp >= PING_INTERVAL_S {
+        sync_info.last_ping_timestamp = now;
+        action_events_writer.write(NetworkAction(Action::Ping(now)));
+    }
+    let sync_info = &mut *sync_info;
+    sync_info.engine_time.sync(&sync_info.clock_sync, now);
+}
+
 fn step_game_engine(
     mut active_game_engine: ResMut<ActiveGameEngine>,
-    sync_info: Res<EngineSyncInfo>,
+    mut sync_info: ResMut<EngineSyncInfo>,
+    mut action_events_writer: EventWriter<NetworkAction>,
     mut info_event_writer: EventWriter<InfoMessage>,
     game_data: Res<GameDataResource>,
     active_player_entity_id: Res<ActivePlayerEntityId>,
+    mut engine_replay: ResMut<EngineReplay>,
 ) {
     let game_data = &game_data.0;
     let engine = &mut active_game_engine.0;
-    let target_step = sync_info.engine_time.expected_step_index();
-    let game_events = if &target_step > engine.step_index() {
-        let step_diff = target_step - engine.step_index();
-        if step_diff >= 30 {
-            // Arbitrarily bound the max fast forward to 600 steps
-            // (10 seconds of game execution)
-            let step_diff_bounded = step_diff.min(600);
-            println!("skipping forward {} steps", step_diff_bounded);
-            &engine.step_to(&(engine.step_index() + &step_diff))
-        } else if step_diff >= 10 {
-            // execute a double step
-            &vec![engine.step(), engine.step()].concat()
-        } else {
-            &engine.step()
-        }
-    } else {
-        println!("skipped step");
-        &vec![]
-        // local engine is ahead of server, skip a step
-    };
-    for event in game_events {
+    // the engine time follows the server clock, see `sync_server_clock`
+    let tick = sync_info.engine_time.tick(engine);
+    if tick.needs_resync && !sync_info.requested_resync {
+        println!(
+            "WARNING: engine is {} steps behind the server, requesting resync",
+            tick.behind
+        );
+        action_events_writer.write(NetworkAction(Action::RequestEngineReload(
+            *engine.id(),
+            *engine.step_index(),
+        )));
+        sync_info.requested_resync = true;
+    }
+    let game_events = tick.game_events;
+    engine_replay.record_step_hash(engine);
+    for (step_index, diagnostic) in engine.drain_diagnostics() {
+        println!("engine diagnostic at step {step_index}: {diagnostic}");
+    }
+    for event in &game_events {
         match &**event {
             GameEvent::Message(_, _) => {
                 // spawn a message in bevy
@@ -186,6 +268,8 @@ fn handle_engine_event(
     mut engine_sync: ResMut<EngineSyncInfo>,
     active_player_entity_id: Res<ActivePlayerEntityId>,
     mut interpolating_entities: ResMut<InterpolatingEntities>,
+    mut engine_replay: ResMut<EngineReplay>,
+    mut action_events_writer: EventWriter<NetworkAction>,
 ) {
     for event in action_events.read() {
         match &event.0 {
@@ -203,7 +287,18 @@ fn handle_engine_event(
                     .iter()
                     .map(|v| (*v).clone())
                     .collect::<Vec<MobEntity>>();
-                engine.integrate_events(events.clone());
+                if let Err(e) = engine.integrate_events(events.clone()) {
+                    println!("WARNING: failed to integrate server events: {e}");
+                    if !engine_sync.requested_resync {
+                        action_events_writer.write(NetworkAction(Action::RequestEngineReload(
+                            *engine.id(),
+                            *engine.step_index(),
+                        )));
+                        engine_sync.requested_resync = true;
+                    }
+                    continue;
+                }
+                engine_replay.record_events(*engine.step_index(), events);
                 interpolate_mobs(
                     last_mobs,
                     engine,
@@ -259,19 +354,10 @@ fn handle_engine_stats(
                         // debug if needed
                         if let Some(server_entities) = entities_maybe {
                             let local_entities = engine.entities_at_step(hash_step_index);
-                            let server_json =
-                                serde_json::to_string_pretty(&server_entities).unwrap();
-                            let local_json = serde_json::to_string_pretty(&local_entities).unwrap();
-                            let diff = similar::TextDiff::from_lines(&local_json, &server_json);
-                            for change in diff.iter_all_changes() {
-                                let sign = match change.tag() {
-                                    similar::ChangeTag::Delete => "-",
-                                    similar::ChangeTag::Insert => "+",
-                                    similar::ChangeTag::Equal => " ",
-                                };
-                                if change.tag() != similar::ChangeTag::Equal {
-                                    println!("diff {}{}", sign, change);
-                                }
+                            for diff in
+                                diff_entity_maps::<KeindGameLogic>(local_entities, server_entities)
+                            {
+                                println!("diff {diff}");
                             }
                         }
                     }
@@ -287,16 +373,35 @@ fn handle_engine_state(
     mut next_state: ResMut<NextState<GameState>>,
     mut engine_sync: ResMut<EngineSyncInfo>,
     mut active_player_entity_id: ResMut<ActivePlayerEntityId>,
+    mut engine_replay: ResMut<EngineReplay>,
+    game_data: Res<GameDataResource>,
 ) {
     for event in action_events.read() {
-        if let Response::EngineState(engine, player_entity_id_maybe, server_step) = &event.0 {
+        if let Response::EngineState(
+            engine,
+            player_entity_id_maybe,
+            server_step,
+            server_timestamp,
+        ) = &event.0
+        {
             active_player_entity_id.0 = Some(*player_entity_id_maybe);
-            *engine_sync = EngineSyncInfo::default();
-            engine_sync.engine_time = keind_time::GameEngineTime::from_step(*server_step, 60);
+            let now = GameEngineTime::now();
+            *engine_sync = EngineSyncInfo {
+                clock_sync: std::mem::take(&mut engine_sync.clock_sync),
+                last_ping_timestamp: engine_sync.last_ping_timestamp,
+                ..Default::default()
+            };
+            engine_sync
+                .clock_sync
+                .set_remote_step(*server_step, *server_timestamp, now);
+            engine_sync.engine_time = GameEngineTime::from_step(*server_step, 60);
+            let engine_sync = &mut *engine_sync;
+            engine_sync.engine_time.sync(&engine_sync.clock_sync, now);
             engine_sync.server_step = *server_step;
             engine_sync.server_step_timestamp = Some(Instant::now());
             active_engine_state.0 = engine.clone();
             let engine = &mut active_engine_state.0;
+            engine_replay.start(engine, &game_data.0);
             if server_step > engine.step_index() {
                 engine.step_to(&server_step);
             }
@@ -6,6 +6,7 @@ use bevy_egui::egui::RichText;
 use bevy_egui::{EguiContextPass, EguiContexts, egui};
 
 use keind::prelude::*;
+use keind_time::ClockSync;
 use keind_time::GameEngineTime;
 
 use crate::plugins::engine::ActiveGameEngine;
@@ -21,6 +22,9 @@ pub struct EngineSyncInfo {
     pub last_fps_timestamp: Option<Instant>,
     pub last_fps_step_index: u64,
     pub engine_time: GameEngineTime,
+    /// Estimate of the server clock, kept across engine changes.
+    pub clock_sync: ClockSync,
+    pub last_ping_timestamp: f64,
 }
 
 #[derive(States, Default, Clone, Eq, PartialEq, Hash, Debug)]
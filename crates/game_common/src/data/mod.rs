@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -89,6 +90,18 @@ impl GameData {
         Self::from_json(raw)
     }
 
+    /// Identifies the data an engine is running with, e.g. in snapshots and
+    /// replays. Maps are hashed in key order.
+    pub fn hash(&self) -> Result<blake3::Hash> {
+        let sorted = (
+            self.maps.iter().collect::<BTreeMap<_, _>>(),
+            self.items.iter().collect::<BTreeMap<_, _>>(),
+            self.mobs.iter().collect::<BTreeMap<_, _>>(),
+            self.npc.iter().collect::<BTreeMap<_, _>>(),
+        );
+        Ok(blake3::hash(&bincode::serialize(&sorted)?))
+    }
+
     pub fn mob_drop_table(&self, mob_type: u64) -> Result<Vec<DropTableData>> {
         match self.mobs.get(&mob_type) {
             Some(data) => Ok(data.drop_table.clone()),
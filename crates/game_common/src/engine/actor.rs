@@ -8,13 +8,9 @@ use keind::prelude::*;
 use crate::prelude::*;
 
 pub fn contains_platform(engine: &GameEngine<KeindGameLogic>, rect: IRect) -> bool {
-    for platform in engine.entities_by_type::<PlatformEntity>() {
-        let intersection = rect.intersect(platform.rect());
-        if intersection.width() >= 1 && intersection.height() >= 1 {
-            return true;
-        }
-    }
-    false
+    !engine
+        .entities_by_type_in_rect::<PlatformEntity>(&rect)
+        .is_empty()
 }
 
 /// Are we standing with a platform beneath us, without a platform immediately above it?
@@ -58,6 +54,32 @@ pub fn can_move_left_right_without_falling(
     )
 }
 
+/// Move an entity a single step at `velocity`, carrying movement smaller
+/// than a unit in `state.position_remainder`. An axis that is blocked by
+/// the map edge or a platform drops its remainder.
+pub fn step_motion(
+    engine: &GameEngine<KeindGameLogic>,
+    body: IRect,
+    velocity: IVec2,
+    state: &mut BaseEntityState,
+) {
+    let disp = state.step_displacement(velocity, STEPS_PER_SECOND as i32);
+    let x = move_x(body, disp.x, engine);
+    let y = move_y(
+        body,
+        disp.y,
+        &engine.entities_by_type::<PlatformEntity>(),
+        engine.size(),
+    );
+    if x - body.min.x != disp.x {
+        state.position_remainder.x = 0;
+    }
+    if y - body.min.y != disp.y {
+        state.position_remainder.y = 0;
+    }
+    state.position = IVec2::new(x, y);
+}
+
 pub fn move_x<G: GameLogic>(body: IRect, dx: i32, engine: &GameEngine<G>) -> i32 {
     if dx == 0 {
         return body.min.x;
@@ -0,0 +1,7 @@
+/// Collision layers of game entities, used for
+/// `BaseEntityState::collision_layers` and `collision_mask`.
+pub const PLAYER: u32 = 1 << 0;
+pub const MOB: u32 = 1 << 1;
+pub const ITEM: u32 = 1 << 2;
+pub const PORTAL: u32 = 1 << 3;
+pub const MOB_DAMAGE: u32 = 1 << 4;
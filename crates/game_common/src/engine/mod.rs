@@ -1,2 +1,3 @@
 pub mod actor;
+pub mod collision_layer;
 pub mod damage_calc;
@@ -1,13 +1,15 @@
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(KeindGameLogic, pub struct EmojiEntity {});
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct EmojiEntity {}
 
 impl SEEntity<KeindGameLogic> for EmojiEntity {
     fn prestep(&self, _engine: &GameEngine<KeindGameLogic>) -> bool {
-        assert!(self.has_system::<AttachSystem>());
-        assert!(self.has_system::<DisappearSystem>());
         false
     }
 }
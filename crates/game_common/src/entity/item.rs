@@ -1,20 +1,22 @@
 use bevy_math::IVec2;
 
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct ItemEntity {
-        pub item_type: u64,
-        pub count: u32,
-        pub disappears_at_step: u64,
-        // pub becomes_public_at_step: u64, // when non-player creator players may pick it up
-        pub position_offset_y: i32,
-        pub is_picked_up: bool,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct ItemEntity {
+    pub item_type: u64,
+    pub count: u32,
+    pub disappears_at_step: u64,
+    // pub becomes_public_at_step: u64, // when non-player creator players may pick it up
+    #[entity(presentation)]
+    pub position_offset_y: i32,
+    pub is_picked_up: bool,
+}
 
 impl ItemEntity {
     pub fn new_item(
@@ -32,6 +34,8 @@ impl ItemEntity {
                 size: IVec2 { x: 25, y: 25 },
                 velocity: IVec2 { x: 0, y: 350 },
                 player_creator_id: Some(player_creator_id),
+                collision_layers: collision_layer::ITEM,
+                ..Default::default()
             },
             systems: vec![
                 RefPointer::new(
@@ -52,11 +56,6 @@ impl ItemEntity {
 }
 
 impl SEEntity<KeindGameLogic> for ItemEntity {
-    fn prestep(&self, _engine: &GameEngine<KeindGameLogic>) -> bool {
-        assert!(self.has_system::<DisappearSystem>());
-        true
-    }
-
     fn step(&self, engine: &GameEngine<KeindGameLogic>, next_self: &mut Self) {
         let step_index = engine.step_index();
         let self_rect = self.rect();
@@ -2,20 +2,21 @@ use std::io::Write;
 
 use bevy_math::IVec2;
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
 const MESSAGE_WIDTH: i32 = 100;
 const MESSAGE_TOP_PADDING: i32 = 10;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct MessageEntity {
-        pub text: String,
-        disappears_at_step: u64,
-        pub creator_id: u128,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEntity {
+    pub text: String,
+    disappears_at_step: u64,
+    pub creator_id: u128,
+}
 
 /// Text centered at a point. Height to be determined by rendering impl
 impl MessageEntity {
@@ -56,11 +57,6 @@ impl MessageEntity {
 }
 
 impl SEEntity<KeindGameLogic> for MessageEntity {
-    fn prestep(&self, _engine: &GameEngine<KeindGameLogic>) -> bool {
-        assert!(self.has_system::<DisappearSystem>());
-        true
-    }
-
     fn step(&self, engine: &GameEngine<KeindGameLogic>, next_self: &mut Self) {
         // Some custom attachment logic
         if let Some(entity) = engine.entity_by_id_untyped(&self.creator_id, None) {
@@ -6,29 +6,33 @@ use db::Ability;
 use db::PlayerStats;
 use keind::prelude::*;
 use rand::RngCore;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
 const KNOCKBACK_STEPS: u64 = 20;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct MobEntity {
-        pub mob_type: u64,
-        pub drop_table: Vec<DropTableData>,
-        weightless_until: Option<u64>,
-        pub moving_sign: i32,
-        moving_until: Option<u64>,
-        // entity it, last hit step index
-        pub aggro_to: Option<(u128, u64)>,
-        pub received_damage_this_step: Vec<u64>,
-        pub receiving_damage_until: Option<u64>,
-        // direction, until
-        pub knockback_until: Option<(i32, u64)>,
-        pub current_health: u64,
-        pub is_dead: bool,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct MobEntity {
+    pub mob_type: u64,
+    pub drop_table: Vec<DropTableData>,
+    weightless_until: Option<u64>,
+    pub moving_sign: i32,
+    moving_until: Option<u64>,
+    // entity it, last hit step index
+    pub aggro_to: Option<(u128, u64)>,
+    #[entity(presentation)]
+    pub received_damage_this_step: Vec<u64>,
+    pub receiving_damage_until: Option<u64>,
+    // direction, until
+    pub knockback_until: Option<(i32, u64)>,
+    pub current_health: u64,
+    pub is_dead: bool,
+    /// The `MobSpawnEntity` that spawned this mob, if any
+    pub spawner_id: Option<u128>,
+}
 
 impl MobEntity {
     // handle movement calculations
@@ -201,28 +205,8 @@ impl SEEntity<KeindGameLogic> for MobEntity {
                     );
                 }
                 if next_self.current_health <= damage_amount {
+                    // items are dropped in on_despawn
                     next_self.is_dead = true;
-                    let mut x_offset = 0i32;
-                    for drop in self
-                        .drop_table
-                        .iter()
-                        .filter_map(|drop_data| drop_data.drop(&mut rng))
-                        .collect::<Vec<_>>()
-                    {
-                        // drop an item
-                        engine.spawn_entity(
-                            ItemEntity::new_item(
-                                rng.random(),
-                                self.center() + IVec2::new(x_offset, 0),
-                                drop.0, // item type
-                                drop.1, // amount
-                                player_entity_id,
-                                step_index,
-                            )
-                            .into(),
-                        );
-                        x_offset += 10;
-                    }
                     break;
                 } else {
                     next_self.current_health -= damage_amount;
@@ -279,19 +263,46 @@ impl SEEntity<KeindGameLogic> for MobEntity {
         let lower_speed_limit = IVec2::new(-150, -350);
         let upper_speed_limit = IVec2::new(150, 700);
         velocity = velocity.clamp(lower_speed_limit, upper_speed_limit);
-        let x_pos = actor::move_x(
-            self.rect(),
-            last_velocity.x / STEPS_PER_SECOND as i32,
-            engine,
-        );
-        let y_pos = actor::move_y(
-            self.rect(),
-            last_velocity.y / STEPS_PER_SECOND as i32,
-            &engine.entities_by_type::<PlatformEntity>(),
-            &engine.size(),
-        );
-        next_self.state.position.x = x_pos;
-        next_self.state.position.y = y_pos;
+        actor::step_motion(engine, body, last_velocity, &mut next_self.state);
         next_self.state.velocity = velocity;
     }
+
+    fn on_despawn(&self, engine: &GameEngine<KeindGameLogic>) {
+        // free our slot in the spawner, dead or not
+        if let Some(spawner_id) = self.spawner_id
+            && engine.entity_by_id_untyped(&spawner_id, None).is_some()
+        {
+            engine.spawn_system(spawner_id, MobDespawnedSystem { mob_id: self.id() }.into());
+        }
+        if !self.is_dead {
+            return;
+        }
+        // drops belong to the player that killed the mob
+        let Some((player_entity_id, _)) = self.aggro_to else {
+            return;
+        };
+        let step_index = engine.step_index();
+        let mut rng = self.rng(step_index);
+        let mut x_offset = 0i32;
+        for drop in self
+            .drop_table
+            .iter()
+            .filter_map(|drop_data| drop_data.drop(&mut rng))
+            .collect::<Vec<_>>()
+        {
+            // drop an item
+            engine.spawn_entity(
+                ItemEntity::new_item(
+                    rng.random(),
+                    self.center() + IVec2::new(x_offset, 0),
+                    drop.0, // item type
+                    drop.1, // amount
+                    player_entity_id,
+                    step_index,
+                )
+                .into(),
+            );
+            x_offset += 10;
+        }
+    }
 }
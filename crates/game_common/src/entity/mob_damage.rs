@@ -4,18 +4,19 @@ use bevy_math::IVec2;
 use keind::prelude::*;
 
 use db::Ability;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct MobDamageEntity {
-        pub attached_to: u128,
-        pub contacted_mob_id: Option<u128>,
-        pub ability: Ability,
-        pub has_despawned: bool,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct MobDamageEntity {
+    pub attached_to: u128,
+    pub contacted_mob_id: Option<u128>,
+    pub ability: Ability,
+    pub has_despawned: bool,
+}
 
 impl MobDamageEntity {
     pub fn new_with_entity(id: u128, entity: &EngineEntity, ability: Ability) -> Self {
@@ -25,6 +26,8 @@ impl MobDamageEntity {
                 position: entity.position(),
                 size: entity.size(),
                 player_creator_id: entity.player_creator_id(),
+                collision_layers: collision_layer::MOB_DAMAGE,
+                collision_mask: collision_layer::MOB,
                 ..Default::default()
             },
             vec![RefPointer::new(
@@ -43,7 +46,6 @@ impl MobDamageEntity {
 
 impl SEEntity<KeindGameLogic> for MobDamageEntity {
     fn prestep(&self, engine: &GameEngine<KeindGameLogic>) -> bool {
-        assert!(self.has_system::<AttachSystem>());
         if self.has_despawned || self.contacted_mob_id.is_some() {
             // despawn the mob damage entity
             let entity = engine
@@ -62,18 +64,20 @@ impl SEEntity<KeindGameLogic> for MobDamageEntity {
             next_self.state.size = attached_entity.size();
             next_self.state.position = attached_entity.position();
             // handle contact with a mob
-            for entity in engine.entities_by_type::<MobEntity>() {
-                if !entity.rect().intersect(self.rect()).is_empty() {
-                    if self.player_creator_id().is_none() {
-                        println!("WARNING: mob damage entity has not player creator!");
-                        continue;
-                    }
-                    next_self.contacted_mob_id = Some(entity.id());
-
-                    // despawn whatever it's attached to
-                    engine.remove_entity(attached_entity.id());
-                    break;
+            let mobs = engine
+                .contacts()
+                .touching(&self.id())
+                .filter_map(|id| engine.entity_by_id::<MobEntity>(id, None));
+            for entity in mobs {
+                if self.player_creator_id().is_none() {
+                    println!("WARNING: mob damage entity has not player creator!");
+                    continue;
                 }
+                next_self.contacted_mob_id = Some(entity.id());
+
+                // despawn whatever it's attached to
+                engine.remove_entity(attached_entity.id());
+                break;
             }
         } else {
             next_self.has_despawned = true;
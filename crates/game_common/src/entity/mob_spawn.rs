@@ -3,18 +3,19 @@ use std::collections::BTreeSet;
 use bevy_math::IVec2;
 use keind::prelude::*;
 use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct MobSpawnEntity {
-        pub spawn_data: MobSpawnData,
-        pub drop_table: Vec<DropTableData>,
-        pub last_spawn_step: u64,
-        owned_mob_ids: BTreeSet<u128>,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct MobSpawnEntity {
+    pub spawn_data: MobSpawnData,
+    pub drop_table: Vec<DropTableData>,
+    pub last_spawn_step: u64,
+    owned_mob_ids: BTreeSet<u128>,
+}
 
 impl MobSpawnEntity {
     pub fn new_data(id: u128, spawn_data: MobSpawnData, drop_table: Vec<DropTableData>) -> Self {
@@ -31,17 +32,17 @@ impl MobSpawnEntity {
         out.drop_table = drop_table;
         out
     }
+
+    /// Stop counting a mob towards `spawn_data.max_count`.
+    pub fn forget_mob(&mut self, mob_id: &u128) {
+        self.owned_mob_ids.remove(mob_id);
+    }
 }
 
 impl SEEntity<KeindGameLogic> for MobSpawnEntity {
     fn step(&self, engine: &GameEngine<KeindGameLogic>, next_self: &mut Self) {
         let step_index = engine.step_index();
         let current_spawn_count = self.owned_mob_ids.len();
-        for id in &self.owned_mob_ids {
-            if !engine.entity_by_id_untyped(id, None).is_some() {
-                next_self.owned_mob_ids.remove(id);
-            }
-        }
 
         if current_spawn_count >= self.spawn_data.max_count {
             return;
@@ -61,6 +62,7 @@ impl SEEntity<KeindGameLogic> for MobSpawnEntity {
                         rng.random_range(self.position().y..self.position().y + self.size().y),
                     ),
                     size: IVec2::new(37, 62),
+                    collision_layers: collision_layer::MOB,
                     ..Default::default()
                 },
                 vec![],
@@ -69,6 +71,7 @@ impl SEEntity<KeindGameLogic> for MobSpawnEntity {
             mob_entity.drop_table = self.drop_table.clone();
             mob_entity.current_health = 10;
             mob_entity.mob_type = self.spawn_data.mob_type;
+            mob_entity.spawner_id = Some(self.id());
             engine.spawn_entity(mob_entity.into());
         }
         next_self.last_spawn_step = *step_index;
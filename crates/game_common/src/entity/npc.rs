@@ -4,17 +4,18 @@ use bevy_math::IVec2;
 use rand::Rng;
 
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct NpcEntity {
-        pub data: NpcData,
-        last_message_step: u64,
-        last_announcement: usize,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct NpcEntity {
+    pub data: NpcData,
+    last_message_step: u64,
+    last_announcement: usize,
+}
 
 impl NpcEntity {
     pub fn new_data(id: u128, position: IVec2, data: NpcData) -> Self {
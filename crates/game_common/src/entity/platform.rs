@@ -1,7 +1,11 @@
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(KeindGameLogic, pub struct PlatformEntity {});
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformEntity {}
 
 impl SEEntity<KeindGameLogic> for PlatformEntity {}
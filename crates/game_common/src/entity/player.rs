@@ -6,6 +6,8 @@ use rand::Rng;
 use db::Ability;
 use db::PlayerRecord;
 use db::PlayerStats;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 use crate::system::weightless::WeightlessSystem;
@@ -13,20 +15,20 @@ use crate::system::weightless::WeightlessSystem;
 const DAMAGE_IFRAME_STEPS: u64 = 120;
 const KNOCKBACK_STEPS: u64 = 10;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct PlayerEntity {
-        pub player_id: String, // the game id, not entity id
-        pub record: PlayerRecord,
-        attacking_until: Option<u64>,
-        pub facing_left: bool,
-        pub showing_emoji_until: Option<u64>,
-        pub stats_ptr: RefPointer<PlayerStats>,
-        pub received_damage_this_step: (bool, u64),
-        // direction, until
-        pub knockback_until: Option<(i32, u64)>,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerEntity {
+    pub player_id: String, // the game id, not entity id
+    pub record: PlayerRecord,
+    attacking_until: Option<u64>,
+    pub facing_left: bool,
+    pub showing_emoji_until: Option<u64>,
+    pub stats_ptr: RefPointer<PlayerStats>,
+    #[entity(presentation)]
+    pub received_damage_this_step: (bool, u64),
+    // direction, until
+    pub knockback_until: Option<(i32, u64)>,
+}
 
 impl PlayerEntity {
     pub fn new_with_ids(id: u128, record: PlayerRecord, stats: PlayerStats) -> Self {
@@ -36,6 +38,10 @@ impl PlayerEntity {
                 position: IVec2::new(100, 100),
                 size: IVec2::new(52, 52),
                 player_creator_id: Some(id),
+                collision_layers: collision_layer::PLAYER,
+                collision_mask: collision_layer::MOB
+                    | collision_layer::ITEM
+                    | collision_layer::PORTAL,
                 ..Default::default()
             },
             player_id: record.id.clone(),
@@ -78,69 +84,70 @@ impl SEEntity<KeindGameLogic> for PlayerEntity {
         let can_jump = actor::on_platform(body, engine);
 
         if !self.has_system::<InvincibleSystem>() {
-            for entity in engine.entities_by_type::<MobEntity>() {
-                if !entity.rect().intersect(self.rect()).is_empty() {
-                    // receiving damage
-                    let knockback_dir = if entity.center().x > self.center().x {
-                        -1
-                    } else {
-                        1
-                    };
-                    next_self.knockback_until = Some((knockback_dir, step_index + KNOCKBACK_STEPS));
-                    next_self.state.velocity.x += knockback_dir * 400;
-                    next_self.state.velocity.y += 100;
+            if let Some(entity) = engine
+                .contacts()
+                .touching(&self.id())
+                .find_map(|id| engine.entity_by_id::<MobEntity>(id, None))
+            {
+                // receiving damage from the lowest id mob we're touching
+                let knockback_dir = if entity.center().x > self.center().x {
+                    -1
+                } else {
+                    1
+                };
+                next_self.knockback_until = Some((knockback_dir, step_index + KNOCKBACK_STEPS));
+                next_self.state.velocity.x += knockback_dir * 400;
+                next_self.state.velocity.y += 100;
+                engine.spawn_system(
+                    self.id(),
+                    InvincibleSystem {
+                        until_step: Some(step_index + DAMAGE_IFRAME_STEPS),
+                    }
+                    .into(),
+                );
+                // engine.spawn_system(
+                //     self.id(),
+                //     RefPointer::new(
+                //         WeightlessSystem {
+                //             until_step: Some(step_index + 3),
+                //         }
+                //         .into(),
+                //     ),
+                // );
+                let damage_amount = damage_calc::compute_damage(
+                    &Ability::Strength,
+                    &PlayerStats::default(),
+                    &*self.stats_ptr,
+                    &mut rng,
+                );
+                next_self.received_damage_this_step = (true, damage_amount);
+                if damage_amount > 0 {
                     engine.spawn_system(
                         self.id(),
-                        InvincibleSystem {
-                            until_step: Some(step_index + DAMAGE_IFRAME_STEPS),
+                        PlayerExpSystem {
+                            record: AbilityExpRecord {
+                                player_id: self.player_id.clone(),
+                                amount: damage_amount,
+                                ability: Ability::Health,
+                            },
                         }
                         .into(),
                     );
-                    // engine.spawn_system(
-                    //     self.id(),
-                    //     RefPointer::new(
-                    //         WeightlessSystem {
-                    //             until_step: Some(step_index + 3),
-                    //         }
-                    //         .into(),
-                    //     ),
-                    // );
-                    let damage_amount = damage_calc::compute_damage(
-                        &Ability::Strength,
-                        &PlayerStats::default(),
-                        &*self.stats_ptr,
-                        &mut rng,
-                    );
-                    next_self.received_damage_this_step = (true, damage_amount);
-                    if damage_amount > 0 {
-                        engine.spawn_system(
-                            self.id(),
-                            PlayerExpSystem {
-                                record: AbilityExpRecord {
-                                    player_id: self.player_id.clone(),
-                                    amount: damage_amount,
-                                    ability: Ability::Health,
-                                },
-                            }
-                            .into(),
-                        );
-                    }
-                    if next_self.record.current_health <= damage_amount {
-                        next_self.record.current_health = 0;
-                        // player has died
-                        // TODO: move to respawn map
-                        engine.register_game_event(GameEvent::PlayerHealth(
-                            next_self.player_id.clone(),
-                            0,
-                        ));
-                    } else {
-                        next_self.record.current_health -= damage_amount;
-                        engine.register_game_event(GameEvent::PlayerHealth(
-                            next_self.player_id.clone(),
-                            next_self.record.current_health,
-                        ));
-                    }
-                    break;
+                }
+                if next_self.record.current_health <= damage_amount {
+                    next_self.record.current_health = 0;
+                    // player has died
+                    // TODO: move to respawn map
+                    engine.register_game_event(GameEvent::PlayerHealth(
+                        next_self.player_id.clone(),
+                        0,
+                    ));
+                } else {
+                    next_self.record.current_health -= damage_amount;
+                    engine.register_game_event(GameEvent::PlayerHealth(
+                        next_self.player_id.clone(),
+                        next_self.record.current_health,
+                    ));
                 }
             }
         }
@@ -193,22 +200,29 @@ impl SEEntity<KeindGameLogic> for PlayerEntity {
                 next_self.facing_left = false;
             }
             if !input.move_left && !input.move_right {
-                // accelerate toward 0.0
-                next_self.state.velocity.x -= next_self.state.velocity.x / 4;
+                // accelerate toward 0.0, stopping once the decay rounds to
+                // nothing so we don't creep forever
+                let decay = next_self.state.velocity.x / 4;
+                next_self.state.velocity.x = if decay == 0 {
+                    0
+                } else {
+                    next_self.state.velocity.x - decay
+                };
             }
         }
         if input.enter_portal {
-            for entity in engine.entities_by_type::<PortalEntity>() {
-                if entity.can_enter(self) {
-                    engine.register_game_event(GameEvent::PlayerEnterPortal {
-                        player_id: self.player_id.clone(),
-                        entity_id: self.id(),
-                        from_map: entity.from.clone(),
-                        to_map: entity.to.clone(),
-                        requested_spawn_pos: None,
-                    });
-                    break;
-                }
+            if let Some(entity) = engine
+                .contacts()
+                .touching(&self.id())
+                .find_map(|id| engine.entity_by_id::<PortalEntity>(id, None))
+            {
+                engine.register_game_event(GameEvent::PlayerEnterPortal {
+                    player_id: self.player_id.clone(),
+                    entity_id: self.id(),
+                    from_map: entity.from.clone(),
+                    to_map: entity.to.clone(),
+                    requested_spawn_pos: None,
+                });
             }
         }
         if input.pick_up {
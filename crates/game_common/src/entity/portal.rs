@@ -1,18 +1,19 @@
 use bevy_math::IVec2;
 
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct PortalEntity {
-        // destination map name
-        #[serde(skip)]
-        pub from: String,
-        pub to: String,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct PortalEntity {
+    // destination map name
+    #[serde(skip)]
+    pub from: String,
+    pub to: String,
+}
 
 impl PortalEntity {
     pub fn new_data(id: u128, map_data: &MapData, portal_data: &PortalData) -> Self {
@@ -23,6 +24,7 @@ impl PortalEntity {
                 id,
                 size: IVec2::new(60, 60),
                 position: portal_data.position,
+                collision_layers: collision_layer::PORTAL,
                 ..Default::default()
             },
             ..Default::default()
@@ -30,12 +32,6 @@ impl PortalEntity {
     }
 }
 
-impl PortalEntity {
-    pub fn can_enter(&self, player: &PlayerEntity) -> bool {
-        !player.rect().intersect(self.rect()).is_empty()
-    }
-}
-
 impl SEEntity<KeindGameLogic> for PortalEntity {
     fn prestep(&self, _engine: &GameEngine<KeindGameLogic>) -> bool {
         false
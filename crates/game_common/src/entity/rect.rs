@@ -1,14 +1,15 @@
 use bevy_math::Vec3;
 
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct RectEntity {
-        pub color: Vec3,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct RectEntity {
+    pub color: Vec3,
+}
 
 impl SEEntity<KeindGameLogic> for RectEntity {}
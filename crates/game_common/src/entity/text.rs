@@ -2,21 +2,22 @@ use bevy_math::IVec2;
 use bevy_math::Vec3;
 
 use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::prelude::*;
 
-entity_struct!(
-    KeindGameLogic,
-    pub struct TextEntity {
-        // entity id, relative position
-        pub attached_to: Option<(u128, IVec2)>,
-        pub disappears_at_step_index: u64,
-        pub text: String,
-        pub font_size: f32,
-        // srgb
-        pub color: Vec3,
-    }
-);
+#[keind::entity(KeindGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct TextEntity {
+    // entity id, relative position
+    pub attached_to: Option<(u128, IVec2)>,
+    pub disappears_at_step_index: u64,
+    pub text: String,
+    pub font_size: f32,
+    // srgb
+    pub color: Vec3,
+}
 
 impl SEEntity<KeindGameLogic> for TextEntity {
     fn prestep(&self, engine: &GameEngine<KeindGameLogic>) -> bool {
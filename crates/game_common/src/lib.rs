@@ -12,6 +12,8 @@ mod engine;
 mod entity;
 mod network;
 mod system;
+#[cfg(test)]
+mod test;
 
 use prelude::*;
 
@@ -53,6 +55,7 @@ pub enum GameEvent {
 
 #[derive(EntitySystem, Debug, Clone, Serialize, Deserialize)]
 pub enum EngineEntitySystem {
+    #[systems(unique)]
     Attach(AttachSystem),
     Disappear(DisappearSystem),
     PlayerExp(PlayerExpSystem),
@@ -60,14 +63,19 @@ pub enum EngineEntitySystem {
     AtomicMove(AtomicMoveSystem),
     Weightless(WeightlessSystem),
     Invincible(InvincibleSystem),
+    MobDespawned(MobDespawnedSystem),
 }
 
 #[derive(EngineEntity, Debug, Clone, Serialize, Deserialize)]
 pub enum EngineEntity {
+    #[systems(require(AttachSystem, DisappearSystem))]
     Emoji(EmojiEntity),
+    #[systems(require(DisappearSystem))]
     Item(ItemEntity),
+    #[systems(require(DisappearSystem))]
     Message(MessageEntity),
     Mob(MobEntity),
+    #[systems(require(AttachSystem))]
     MobDamage(MobDamageEntity),
     MobSpawn(MobSpawnEntity),
     Npc(NpcEntity),
@@ -86,6 +94,7 @@ pub struct KeindGameLogic;
 impl GameLogic for KeindGameLogic {
     type Entity = EngineEntity;
     type System = EngineEntitySystem;
+    type WorldSystem = ();
     type Event = GameEvent;
     type Input = EntityInput;
 
@@ -109,25 +118,22 @@ impl GameLogic for KeindGameLogic {
                     if let Some(player_entity) =
                         engine.entity_by_id::<PlayerEntity>(player_entity_id, None)
                     {
-                        let mut item_id_maybe = None;
-                        for item in engine.entities_by_type::<ItemEntity>() {
-                            // if the player doesn't intersect the item ignore it
-                            if item.rect().intersect(player_entity.rect()).is_empty() {
-                                continue;
-                            }
-                            // otherwise pick up the item
-                            // mark the item for removal
-                            item_id_maybe = Some(item.id());
-
-                            // register an event that will be handled by an external
-                            // observer
-                            engine.register_game_event(GameEvent::PlayerPickUp(
-                                player_entity.player_id.clone(),
-                                item.item_type,
-                                item.count,
-                            ));
-                            break;
-                        }
+                        // pick up the first item the player intersects
+                        let item_id_maybe = engine
+                            .contacts()
+                            .touching(&player_entity.id())
+                            .find_map(|id| engine.entity_by_id::<ItemEntity>(id, None))
+                            .map(|item| {
+                                // register an event that will be handled by an external
+                                // observer
+                                engine.register_game_event(GameEvent::PlayerPickUp(
+                                    player_entity.player_id.clone(),
+                                    item.item_type,
+                                    item.count,
+                                ));
+                                // mark the item for removal
+                                item.id()
+                            });
                         // remove the item immediately so if other pick up requests
                         // occured this step they don't pick up the same item
                         if let Some(item_id) = item_id_maybe {
@@ -141,6 +147,36 @@ impl GameLogic for KeindGameLogic {
             }
         }
     }
+
+    fn validate_remote_event(
+        _engine: &GameEngine<Self>,
+        sender: &u128,
+        _step_index: &u64,
+        event: &EngineEvent<Self>,
+    ) -> RemoteEventVerdict<Self> {
+        match event {
+            EngineEvent::Input {
+                input,
+                entity_id,
+                is_non_determinism,
+            } if entity_id == sender => {
+                if *is_non_determinism {
+                    RemoteEventVerdict::Accept
+                } else {
+                    // inputs from clients must survive rewinds
+                    RemoteEventVerdict::Transform(EngineEvent::Input {
+                        input: input.clone(),
+                        entity_id: *entity_id,
+                        is_non_determinism: true,
+                    })
+                }
+            }
+            EngineEvent::Input { .. } => {
+                RemoteEventVerdict::Reject("input for another entity".to_string())
+            }
+            _ => RemoteEventVerdict::Reject("clients may only send inputs".to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -173,6 +209,9 @@ pub static STEP_DELAY: u64 = 60;
 pub static STEPS_PER_SECOND: u32 = 60;
 pub static STEP_LEN_S: f32 = 1.0 / STEPS_PER_SECOND as f32;
 
+// when set, engines record replays into this directory
+pub static REPLAY_DIR_ENV: &str = "KEIND_REPLAY_DIR";
+
 // Custom deserializer for Vec2
 pub fn deserialize_vec2<'de, D>(deserializer: D) -> Result<bevy_math::IVec2, D::Error>
 where
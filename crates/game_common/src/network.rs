@@ -24,7 +24,8 @@ pub enum Action {
     PlayerInventorySwap((u8, u8)),
     // slot index, count to drop
     PlayerInventoryDrop(u8, u32),
-    Ping,
+    // client time the ping was sent, see `keind_time::PingSample`
+    Ping(f64),
 }
 
 /// Types of messages the client can receive from the server
@@ -32,13 +33,14 @@ pub enum Action {
 pub enum Response {
     PlayerLoggedIn(PlayerRecord),
     PlayerRemoved(String),
-    // engine, entity id the player controls, server step
-    EngineState(GameEngine<KeindGameLogic>, u128, u64),
+    // engine, entity id the player controls, server step, server time the
+    // step was reached
+    EngineState(GameEngine<KeindGameLogic>, u128, u64, f64),
     EngineStats(
         u128,
         u64,
         (u64, blake3::Hash),
-        Option<BTreeMap<u128, RefPointer<EngineEntity>>>,
+        Option<PersistentMap<u128, RefPointer<EngineEntity>>>,
     ),
     // engine id, game events <step_index, events>, server step
     RemoteEngineEvents(u128, BTreeMap<u64, Vec<EngineEvent<KeindGameLogic>>>, u64),
@@ -50,6 +52,8 @@ pub enum Response {
     // from_map
     PlayerExitMap(String),
     LoginError(String),
-    Pong,
+    // client time the ping was sent, server time it was received, server
+    // time the pong was sent
+    Pong(f64, f64, f64),
     Tick,
 }
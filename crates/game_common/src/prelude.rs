@@ -2,6 +2,7 @@ pub use std::any::Any;
 pub use std::any::TypeId;
 
 pub use crate::AnimationData;
+pub use crate::REPLAY_DIR_ENV;
 pub use crate::STEP_DELAY;
 pub use crate::STEP_LEN_S;
 pub use crate::STEPS_PER_SECOND;
@@ -18,6 +19,7 @@ pub use crate::deserialize_vec2;
 
 // Engine
 pub use crate::engine::actor;
+pub use crate::engine::collision_layer;
 pub use crate::engine::damage_calc;
 
 // Entities
@@ -40,5 +42,6 @@ pub use crate::system::attach::AttachSystem;
 pub use crate::system::disappear::DisappearSystem;
 pub use crate::system::gravity::GravitySystem;
 pub use crate::system::invincible::InvincibleSystem;
+pub use crate::system::mob_despawned::MobDespawnedSystem;
 pub use crate::system::player_exp::PlayerExpSystem;
 pub use crate::system::weightless::WeightlessSystem;
@@ -40,6 +40,10 @@ impl AtomicMoveSystem {
 }
 
 impl EEntitySystem<KeindGameLogic> for AtomicMoveSystem {
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Physics
+    }
+
     fn prestep(
         &self,
         _engine: &GameEngine<KeindGameLogic>,
@@ -65,20 +69,11 @@ impl EEntitySystem<KeindGameLogic> for AtomicMoveSystem {
             .velocity()
             .clamp(lower_speed_limit, upper_speed_limit);
 
-        let velocity = entity.velocity();
-
-        let body = entity.rect();
-        // approximate displacement using integer math
-        let disp = IVec2::new(
-            velocity.x / STEPS_PER_SECOND as i32,
-            velocity.y / STEPS_PER_SECOND as i32,
-        );
-        next_entity.state_mut().position.x = actor::move_x(body, disp.x, engine);
-        next_entity.state_mut().position.y = actor::move_y(
-            body,
-            disp.y,
-            &engine.entities_by_type::<PlatformEntity>(),
-            engine.size(),
+        actor::step_motion(
+            engine,
+            entity.rect(),
+            entity.velocity(),
+            next_entity.state_mut(),
         );
         Some(self.clone())
     }
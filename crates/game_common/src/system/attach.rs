@@ -13,20 +13,11 @@ pub struct AttachSystem {
 }
 
 impl EEntitySystem<KeindGameLogic> for AttachSystem {
-    fn prestep(&self, engine: &GameEngine<KeindGameLogic>, entity: &EngineEntity) -> bool {
-        // only allow one of these per entity
-        assert_eq!(
-            entity
-                .systems()
-                .iter()
-                .filter_map(|system| system.extract_ref::<Self>())
-                .collect::<Vec<_>>()
-                .len(),
-            1,
-            "multiple attach systems on entity"
-        );
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::PostPhysics
+    }
 
-        // TODO: assert no duplicate system on entity
+    fn prestep(&self, engine: &GameEngine<KeindGameLogic>, entity: &EngineEntity) -> bool {
         // check if entity positions are equal
         // if yes don't step
         if let Some(entity_0) = engine.entity_by_id_untyped(&self.attached_to, None)
@@ -11,6 +11,10 @@ pub struct DisappearSystem {
 }
 
 impl EEntitySystem<KeindGameLogic> for DisappearSystem {
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Cleanup
+    }
+
     fn prestep(&self, engine: &GameEngine<KeindGameLogic>, entity: &EngineEntity) -> bool {
         if engine.step_index() == &self.at_step {
             engine.remove_entity(entity.id());
@@ -11,6 +11,10 @@ use crate::prelude::*;
 pub struct GravitySystem;
 
 impl EEntitySystem<KeindGameLogic> for GravitySystem {
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::PrePhysics
+    }
+
     fn prestep(
         &self,
         _engine: &GameEngine<KeindGameLogic>,
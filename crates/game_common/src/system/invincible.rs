@@ -12,6 +12,10 @@ pub struct InvincibleSystem {
 }
 
 impl EEntitySystem<KeindGameLogic> for InvincibleSystem {
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Cleanup
+    }
+
     fn prestep(
         &self,
         engine: &GameEngine<KeindGameLogic>,
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use keind::prelude::*;
+
+use crate::prelude::*;
+
+/// Tell a mob spawner that one of its mobs despawned so
+/// it can spawn a replacement. Attached by `MobEntity::on_despawn`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MobDespawnedSystem {
+    pub mob_id: u128,
+}
+
+impl EEntitySystem<KeindGameLogic> for MobDespawnedSystem {
+    fn step(
+        &self,
+        _engine: &GameEngine<KeindGameLogic>,
+        _entity: &EngineEntity,
+        next_entity: &mut EngineEntity,
+    ) -> Option<Self> {
+        next_entity
+            .extract_mut::<MobSpawnEntity>()
+            .expect("MobDespawnedSystem must be attached to a mob spawn entity")
+            .forget_mob(&self.mob_id);
+
+        // Despawn
+        None
+    }
+}
@@ -5,5 +5,6 @@ pub mod attach;
 pub mod disappear;
 pub mod gravity;
 pub mod invincible;
+pub mod mob_despawned;
 pub mod player_exp;
 pub mod weightless;
@@ -14,6 +14,10 @@ pub struct PlayerExpSystem {
 }
 
 impl EEntitySystem<KeindGameLogic> for PlayerExpSystem {
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::PostPhysics
+    }
+
     fn step(
         &self,
         engine: &GameEngine<KeindGameLogic>,
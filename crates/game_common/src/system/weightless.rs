@@ -12,6 +12,10 @@ pub struct WeightlessSystem {
 }
 
 impl EEntitySystem<KeindGameLogic> for WeightlessSystem {
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Cleanup
+    }
+
     fn prestep(
         &self,
         engine: &GameEngine<KeindGameLogic>,
@@ -0,0 +1,50 @@
+use bevy_math::IVec2;
+use keind::prelude::*;
+
+use crate::prelude::*;
+
+fn spawner_engine(max_count: usize) -> GameEngine<KeindGameLogic> {
+    let engine = GameEngine::<KeindGameLogic>::new(IVec2::new(1000, 1000), 0);
+    let spawner = MobSpawnEntity::new_data(
+        1,
+        MobSpawnData {
+            position: IVec2::new(100, 100),
+            size: IVec2::new(100, 100),
+            mob_type: 0,
+            max_count,
+        },
+        vec![],
+    );
+    engine.spawn_entity(spawner.into());
+    engine
+}
+
+fn mob_ids(engine: &GameEngine<KeindGameLogic>) -> Vec<u128> {
+    engine
+        .entities_by_type::<MobEntity>()
+        .into_iter()
+        .map(|mob| mob.id())
+        .collect()
+}
+
+#[test]
+fn should_replace_despawned_mobs() {
+    let mut engine = spawner_engine(1);
+    engine.step_to(&100);
+    let spawned = mob_ids(&engine);
+    assert_eq!(spawned.len(), 1);
+
+    engine.remove_entity(spawned[0]);
+    engine.step_to(&200);
+    let respawned = mob_ids(&engine);
+    assert_eq!(respawned.len(), 1);
+    assert_ne!(respawned, spawned);
+    assert_eq!(engine.diagnostics().count(), 0);
+}
+
+#[test]
+fn should_not_exceed_max_count() {
+    let mut engine = spawner_engine(3);
+    engine.step_to(&300);
+    assert_eq!(mob_ids(&engine).len(), 3);
+}
@@ -0,0 +1,2 @@
+mod mob_spawn;
+mod remote;
@@ -0,0 +1,76 @@
+use keind::prelude::*;
+
+use crate::prelude::*;
+
+const SENDER: u128 = 7;
+
+fn input(entity_id: u128, is_non_determinism: bool) -> EngineEvent<KeindGameLogic> {
+    EngineEvent::Input {
+        input: EntityInput {
+            move_left: true,
+            ..Default::default()
+        },
+        entity_id,
+        is_non_determinism,
+    }
+}
+
+fn validate(event: &EngineEvent<KeindGameLogic>) -> RemoteEventVerdict<KeindGameLogic> {
+    let engine = GameEngine::<KeindGameLogic>::default();
+    KeindGameLogic::validate_remote_event(&engine, &SENDER, &0, event)
+}
+
+#[test]
+fn should_accept_input_for_own_entity() {
+    assert!(matches!(
+        validate(&input(SENDER, true)),
+        RemoteEventVerdict::Accept
+    ));
+}
+
+#[test]
+fn should_make_own_input_survive_rewinds() {
+    let RemoteEventVerdict::Transform(EngineEvent::Input {
+        input,
+        entity_id,
+        is_non_determinism,
+    }) = validate(&input(SENDER, false))
+    else {
+        panic!("expected the input to be transformed");
+    };
+    assert_eq!(entity_id, SENDER);
+    assert!(is_non_determinism);
+    assert!(input.move_left);
+}
+
+#[test]
+fn should_reject_input_for_another_entity() {
+    assert!(matches!(
+        validate(&input(SENDER + 1, true)),
+        RemoteEventVerdict::Reject(_)
+    ));
+}
+
+#[test]
+fn should_reject_events_other_than_input() {
+    let remove = EngineEvent::RemoveEntity {
+        entity_id: SENDER,
+        is_non_determinism: true,
+    };
+    assert!(matches!(validate(&remove), RemoteEventVerdict::Reject(_)));
+    let spawn = EngineEvent::SpawnEntity {
+        entity: RefPointer::new(
+            EmojiEntity::new(
+                BaseEntityState {
+                    id: 1,
+                    player_creator_id: Some(SENDER),
+                    ..Default::default()
+                },
+                vec![],
+            )
+            .into(),
+        ),
+        is_non_determinism: true,
+    };
+    assert!(matches!(validate(&spawn), RemoteEventVerdict::Reject(_)));
+}
@@ -0,0 +1,180 @@
+//! Entities and game logic shared by the benches.
+
+// each bench uses a part of this module
+#![allow(dead_code)]
+
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::sync::atomic::AtomicIsize;
+use std::sync::atomic::Ordering;
+
+use keind::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Counts the bytes currently allocated, for benches measuring memory.
+/// Register it with `#[global_allocator]` and read it with `allocated`.
+pub struct CountingAllocator;
+
+static ALLOCATED: AtomicIsize = AtomicIsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size() as isize, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size() as isize, Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Bytes allocated through `CountingAllocator` and not freed.
+pub fn allocated() -> isize {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BenchInput {
+    pub move_right: bool,
+}
+
+#[keind::entity(BenchGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct StaticEntity {}
+
+impl SEEntity<BenchGameLogic> for StaticEntity {
+    fn prestep(&self, _engine: &GameEngine<BenchGameLogic>) -> bool {
+        false
+    }
+}
+
+#[keind::entity(BenchGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct MovingEntity {}
+
+impl SEEntity<BenchGameLogic> for MovingEntity {
+    fn step(&self, engine: &GameEngine<BenchGameLogic>, next_self: &mut Self) {
+        let dx = if engine.input_for_entity(&self.id()).move_right {
+            1
+        } else {
+            -1
+        };
+        next_self.state.position.x = (self.position().x + dx).rem_euclid(engine.size().x);
+    }
+}
+
+/// Walks back and forth, turning around every few seconds by registering
+/// an input for itself, like a mob in game_common.
+#[keind::entity(BenchGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct MobEntity {}
+
+impl SEEntity<BenchGameLogic> for MobEntity {
+    fn step(&self, engine: &GameEngine<BenchGameLogic>, next_self: &mut Self) {
+        let input = engine.input_for_entity(&self.id());
+        let dx = if input.move_right { 1 } else { -1 };
+        next_self.state.position.x = (self.position().x + dx).rem_euclid(engine.size().x);
+        if self.rng(engine.step_index()).random_ratio(1, 120) {
+            engine.register_event(
+                None,
+                EngineEvent::Input {
+                    input: BenchInput {
+                        move_right: !input.move_right,
+                    },
+                    entity_id: self.id(),
+                    is_non_determinism: false,
+                },
+            );
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, EngineEntity)]
+pub enum EngineEntity {
+    Static(StaticEntity),
+    Moving(MovingEntity),
+    Mob(MobEntity),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoopSystem;
+
+impl EEntitySystem<BenchGameLogic> for NoopSystem {}
+
+#[derive(Clone, Debug, Serialize, Deserialize, EntitySystem)]
+pub enum EngineEntitySystem {
+    Noop(NoopSystem),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BenchGameLogic;
+
+impl GameLogic for BenchGameLogic {
+    type Entity = EngineEntity;
+    type System = EngineEntitySystem;
+    type WorldSystem = ();
+    type Event = ();
+    type Input = BenchInput;
+
+    fn handle_game_events(
+        _engine: &mut GameEngine<Self>,
+        _game_events: &Vec<RefPointer<Self::Event>>,
+    ) {
+    }
+}
+
+pub fn build_engine(
+    static_count: u128,
+    moving_count: u128,
+    trailing_state_len: u64,
+) -> GameEngine<BenchGameLogic> {
+    let mut engine = GameEngine::<BenchGameLogic>::new(IVec2::new(1000, 1000), 1);
+    engine.trailing_state_len = trailing_state_len;
+    for id in 1..=(static_count + moving_count) {
+        let state = BaseEntityState {
+            id,
+            position: IVec2::new((id % 1000) as i32, 100),
+            size: IVec2::new(10, 10),
+            ..Default::default()
+        };
+        let entity: EngineEntity = if id <= static_count {
+            StaticEntity::new(state, vec![]).into()
+        } else {
+            MovingEntity::new(state, vec![]).into()
+        };
+        engine.spawn_entity(entity);
+        if id > static_count {
+            engine.register_event(
+                None,
+                EngineEvent::Input {
+                    input: BenchInput {
+                        move_right: id % 2 == 0,
+                    },
+                    entity_id: id,
+                    is_non_determinism: false,
+                },
+            );
+        }
+    }
+    engine.step();
+    engine
+}
+
+/// Spawn `count` mobs with ids following the entities of the engine.
+pub fn spawn_mobs(engine: &mut GameEngine<BenchGameLogic>, count: u128) {
+    let first_id = engine.entity_count() as u128 + 1;
+    for id in first_id..(first_id + count) {
+        let state = BaseEntityState {
+            id,
+            position: IVec2::new((id % 1000) as i32, 300),
+            size: IVec2::new(10, 10),
+            ..Default::default()
+        };
+        engine.spawn_entity(MobEntity::new(state, vec![]).into());
+    }
+    engine.step();
+}
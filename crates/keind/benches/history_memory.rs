@@ -0,0 +1,80 @@
+/// Memory retained per step of engine history.
+///
+/// Compares the engine history (persistent maps with shared structure)
+/// against the previous strategy of cloning the full entity, entity hash,
+/// and input maps every step. Over 360 steps:
+///
+/// ```text
+///   static   moving   full clone (bytes)   persistent (bytes)
+///       50       50                21014                19007
+///      300       50                54236                19277
+///     1000       50               158297                19137
+///      300      300               130649               110875
+/// ```
+///
+/// `cargo bench -p keind --bench history_memory`
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use keind::prelude::*;
+
+mod common;
+
+use common::*;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Bytes retained per step with the engine's own history.
+fn persistent_bytes_per_step(static_count: u128, moving_count: u128, steps: u64) -> isize {
+    let mut engine = build_engine(static_count, moving_count, steps);
+    let before = allocated();
+    for _ in 0..steps {
+        engine.step();
+    }
+    (allocated() - before) / steps as isize
+}
+
+/// Bytes retained per step when the full maps are cloned every step. The
+/// engine keeps no history of its own, the clones hold the same entities.
+fn full_clone_bytes_per_step(static_count: u128, moving_count: u128, steps: u64) -> isize {
+    let mut engine = build_engine(static_count, moving_count, 0);
+    let mut history = VecDeque::new();
+    let before = allocated();
+    for _ in 0..steps {
+        engine.step();
+        let step_index = *engine.step_index();
+        let entities = engine
+            .entities_at_step(&step_index)
+            .iter()
+            .map(|(id, entity)| (*id, entity.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let hashes = entities
+            .iter()
+            .map(|(id, entity)| (*id, GameEngine::<BenchGameLogic>::hash_entity(entity)))
+            .collect::<BTreeMap<_, _>>();
+        let inputs = entities
+            .keys()
+            .map(|id| (*id, engine.input_for_entity(id).clone()))
+            .collect::<HashMap<_, _>>();
+        history.push_back((entities, hashes, inputs));
+    }
+    let out = (allocated() - before) / steps as isize;
+    drop(history);
+    out
+}
+
+fn main() {
+    const STEPS: u64 = 360;
+    println!("history memory per step over {STEPS} steps");
+    println!(
+        "{:>8} {:>8} {:>20} {:>20}",
+        "static", "moving", "full clone (bytes)", "persistent (bytes)"
+    );
+    for (static_count, moving_count) in [(50, 50), (300, 50), (1000, 50), (300, 300)] {
+        let full = full_clone_bytes_per_step(static_count, moving_count, STEPS);
+        let persistent = persistent_bytes_per_step(static_count, moving_count, STEPS);
+        println!("{static_count:>8} {moving_count:>8} {full:>20} {persistent:>20}");
+    }
+}
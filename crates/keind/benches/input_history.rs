@@ -0,0 +1,75 @@
+/// Memory and time per step on a map where 50 mobs set their own inputs
+/// through `register_event`, and time to look up the input of a mob
+/// `LOOKUP_STEPS` back. Run on two revisions to compare input history
+/// strategies:
+///
+/// ```text
+///   a map of inputs per step
+///     static   bytes/step   step (us)   lookup (ns)
+///          0        18946          88            29
+///        300        19285         141            30
+///       1000        19380         174            22
+///
+///   changes since the oldest step
+///          0        18862          76            15
+///        300        19238         102           139
+///       1000        19293         161           160
+///
+///   changes with a checkpoint every 30 steps
+///          0        18909          77            51
+///        300        19240         108            44
+///       1000        19323         136            48
+/// ```
+///
+/// `cargo bench -p keind --bench input_history`
+use std::time::Instant;
+
+mod common;
+
+use common::*;
+
+const MOBS: u128 = 50;
+const STEPS: u64 = 360;
+const TIMED_STEPS: u64 = 3600;
+const LOOKUP_STEPS: u64 = 300;
+const LOOKUPS: u32 = 1000;
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn main() {
+    println!("{MOBS} mobs setting inputs, {STEPS} steps of history");
+    println!(
+        "{:>8} {:>20} {:>20} {:>20}",
+        "static", "history (bytes/step)", "step (us)", "lookup (ns)"
+    );
+    for static_count in [0, 300, 1000] {
+        let mut engine = build_engine(static_count, 0, STEPS);
+        spawn_mobs(&mut engine, MOBS);
+
+        // fill the history
+        let before = allocated();
+        for _ in 0..STEPS {
+            engine.step();
+        }
+        let bytes = (allocated() - before) / STEPS as isize;
+
+        // history is full, old steps are dropped as new ones are recorded
+        let start = Instant::now();
+        for _ in 0..TIMED_STEPS {
+            engine.step();
+        }
+        let micros = start.elapsed().as_micros() / TIMED_STEPS as u128;
+
+        let step_index = engine.step_index() - LOOKUP_STEPS;
+        let mob_ids = (static_count + 1)..=(static_count + MOBS);
+        let start = Instant::now();
+        for _ in 0..LOOKUPS {
+            for id in mob_ids.clone() {
+                std::hint::black_box(engine.input_for_entity_at_step(&step_index, &id));
+            }
+        }
+        let lookup_nanos = start.elapsed().as_nanos() / (LOOKUPS as u128 * MOBS);
+        println!("{static_count:>8} {bytes:>20} {micros:>20} {lookup_nanos:>20}");
+    }
+}
@@ -0,0 +1,96 @@
+/// Time to integrate an input arriving 60 steps late.
+///
+/// Compares rewinding the engine in place against building a past engine
+/// with `engine_at_step`, stepping it forward, and taking its state. Both
+/// replay the late steps, which is most of the time, so they measure
+/// within noise of each other. `engine_at_step` alone is the most an
+/// in-place rewind saves:
+///
+/// ```text
+///   static   moving   rebuilt (us)   in place (us)   engine_at_step (us)
+///       50       50           5222            5015                   129
+///      300       50           5239            4836                   184
+///     1000       50           8162            8242                   509
+///      300      300          26265           24835                   445
+/// ```
+///
+/// `cargo bench -p keind --bench late_events`
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::time::Instant;
+
+use keind::prelude::*;
+
+mod common;
+
+use common::*;
+
+const LATE_STEPS: u64 = 60;
+const ROUNDS: u32 = 100;
+
+/// An input for one of the moving entities, `LATE_STEPS` in the past.
+fn late_input(
+    engine: &GameEngine<BenchGameLogic>,
+    static_count: u128,
+    moving_count: u128,
+    round: u32,
+) -> BTreeMap<u64, Vec<EngineEvent<BenchGameLogic>>> {
+    let entity_id = static_count + 1 + (round as u128 % moving_count);
+    let event = EngineEvent::Input {
+        input: BenchInput {
+            move_right: round % 2 == 0,
+        },
+        entity_id,
+        is_non_determinism: true,
+    };
+    BTreeMap::from([(engine.step_index() - LATE_STEPS, vec![event])])
+}
+
+fn integrate(
+    static_count: u128,
+    moving_count: u128,
+    rewind: impl Fn(&mut GameEngine<BenchGameLogic>, BTreeMap<u64, Vec<EngineEvent<BenchGameLogic>>>),
+) -> Duration {
+    let mut engine = build_engine(static_count, moving_count, 360);
+    engine.step_to(&(2 * LATE_STEPS));
+    let mut total = Duration::ZERO;
+    for round in 0..ROUNDS {
+        let events = late_input(&engine, static_count, moving_count, round);
+        let start = Instant::now();
+        rewind(&mut engine, events);
+        total += start.elapsed();
+        engine.step();
+    }
+    total / ROUNDS
+}
+
+fn main() {
+    println!("integrating an input {LATE_STEPS} steps late, mean of {ROUNDS} rounds");
+    println!(
+        "{:>8} {:>8} {:>20} {:>20} {:>20}",
+        "static", "moving", "rebuilt (us)", "in place (us)", "engine_at_step (us)"
+    );
+    for (static_count, moving_count) in [(50, 50), (300, 50), (1000, 50), (300, 300)] {
+        let rebuilt = integrate(static_count, moving_count, |engine, events| {
+            let to_step_index = *engine.step_index();
+            let from_step_index = *events.first_key_value().unwrap().0;
+            let mut past = engine.engine_at_step(&from_step_index, true).unwrap();
+            past.integrate_events(events).unwrap();
+            past.step_to(&to_step_index);
+            *engine = past;
+        });
+        let in_place = integrate(static_count, moving_count, |engine, events| {
+            engine.integrate_events(events).unwrap();
+        });
+        let engine_at_step = integrate(static_count, moving_count, |engine, events| {
+            let from_step_index = *events.first_key_value().unwrap().0;
+            engine.engine_at_step(&from_step_index, true).unwrap();
+        });
+        println!(
+            "{static_count:>8} {moving_count:>8} {:>20} {:>20} {:>20}",
+            rebuilt.as_micros(),
+            in_place.as_micros(),
+            engine_at_step.as_micros()
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::any::TypeId;
+use std::any::type_name;
+use std::fmt;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// A type of entity system, used to declare which systems an entity may
+/// have attached. See `EEntity::required_systems` and
+/// `EEntitySystem::unique`.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemKind {
+    type_id: TypeId,
+    name: &'static str,
+}
+
+impl SystemKind {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: type_name::<T>(),
+        }
+    }
+
+    /// The name of the system type, without its module path.
+    pub fn name(&self) -> &'static str {
+        self.name.rsplit("::").next().unwrap_or(self.name)
+    }
+}
+
+impl PartialEq for SystemKind {
+    fn eq(&self, other: &Self) -> bool {
+        self.type_id == other.type_id
+    }
+}
+
+impl Eq for SystemKind {}
+
+/// A system constraint broken by a spawned entity or system.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SystemViolation {
+    /// A required system is not attached.
+    Missing { system: String },
+    /// A forbidden system is attached.
+    Forbidden { system: String },
+    /// A unique system is attached more than once.
+    Duplicate { system: String },
+}
+
+impl fmt::Display for SystemViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemViolation::Missing { system } => write!(f, "missing required system {system}"),
+            SystemViolation::Forbidden { system } => write!(f, "has forbidden system {system}"),
+            SystemViolation::Duplicate { system } => {
+                write!(f, "has unique system {system} more than once")
+            }
+        }
+    }
+}
+
+/// Check the constraints declared by `entity` and each of its systems
+/// against the systems attached to it.
+pub(crate) fn check_systems<G: GameLogic>(entity: &G::Entity) -> Result<(), SystemViolation> {
+    let kinds = entity
+        .systems()
+        .iter()
+        .map(|system| system.kind())
+        .collect::<Vec<_>>();
+    let mut required = entity.required_systems();
+    let mut forbidden = entity.forbidden_systems();
+    for (i, system) in entity.systems().iter().enumerate() {
+        if system.unique() && kinds[..i].contains(&kinds[i]) {
+            return Err(SystemViolation::Duplicate {
+                system: kinds[i].name().to_string(),
+            });
+        }
+        required.extend(system.required_systems());
+        forbidden.extend(system.forbidden_systems());
+    }
+    if let Some(kind) = required.iter().find(|kind| !kinds.contains(kind)) {
+        return Err(SystemViolation::Missing {
+            system: kind.name().to_string(),
+        });
+    }
+    if let Some(kind) = forbidden.iter().find(|kind| kinds.contains(kind)) {
+        return Err(SystemViolation::Forbidden {
+            system: kind.name().to_string(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// Ids of the entities touching each entity.
+type ContactMap = BTreeMap<u128, BTreeSet<u128>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactPhase {
+    /// The entities started touching this step.
+    Begin,
+    /// The entities were touching in the previous step and still are.
+    Continue,
+    /// The entities were touching in the previous step and no longer are.
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contact {
+    pub other: u128,
+    pub phase: ContactPhase,
+}
+
+impl Contact {
+    pub fn is_touching(&self) -> bool {
+        self.phase != ContactPhase::End
+    }
+}
+
+/// Overlapping entity pairs, computed once at the end of each step.
+///
+/// Two entities are in contact when their rects intersect and either
+/// entity's `collision_mask` includes a layer of the other's
+/// `collision_layers`. Entities with an empty mask never look for
+/// contacts, but may still be found by others. Like the rest of the
+/// engine state, entities stepping read the contacts of the previous step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Contacts {
+    current: RefPointer<ContactMap>,
+    previous: RefPointer<ContactMap>,
+}
+
+impl Contacts {
+    /// Compute the contacts between `entities`, following `previous`.
+    pub(crate) fn compute<G: GameLogic>(
+        entities: &PersistentMap<u128, RefPointer<G::Entity>>,
+        spatial_index: &SpatialIndex,
+        previous: &Contacts,
+    ) -> Self {
+        let mut current = ContactMap::new();
+        for (id, entity) in entities.iter() {
+            let mask = entity.state().collision_mask;
+            if mask == 0 {
+                continue;
+            }
+            for other_id in spatial_index.query(&SpatialIndex::entity_rect::<G>(entity)) {
+                if &other_id == id {
+                    continue;
+                }
+                let Some(other) = entities.get(&other_id) else {
+                    continue;
+                };
+                if other.state().collision_layers & mask == 0 {
+                    continue;
+                }
+                current.entry(*id).or_default().insert(other_id);
+                current.entry(other_id).or_default().insert(*id);
+            }
+        }
+        Self {
+            current: RefPointer::new(current),
+            previous: previous.current.clone(),
+        }
+    }
+
+    /// Contacts of `id` ordered by the id of the other entity, including
+    /// contacts that ended this step.
+    pub fn of(&self, id: &u128) -> Vec<Contact> {
+        let empty = BTreeSet::new();
+        let current = self.current.get(id).unwrap_or(&empty);
+        let previous = self.previous.get(id).unwrap_or(&empty);
+        current
+            .union(previous)
+            .map(|other| Contact {
+                other: *other,
+                phase: match (current.contains(other), previous.contains(other)) {
+                    (true, true) => ContactPhase::Continue,
+                    (true, false) => ContactPhase::Begin,
+                    _ => ContactPhase::End,
+                },
+            })
+            .collect()
+    }
+
+    /// Ids of the entities touching `id`, in ascending order.
+    pub fn touching(&self, id: &u128) -> impl Iterator<Item = &u128> + use<'_> {
+        self.current.get(id).into_iter().flatten()
+    }
+
+    pub fn is_touching(&self, id: &u128, other: &u128) -> bool {
+        self.current
+            .get(id)
+            .map(|ids| ids.contains(other))
+            .unwrap_or(false)
+    }
+}
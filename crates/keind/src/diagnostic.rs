@@ -0,0 +1,109 @@
+use std::fmt;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// The most diagnostics kept before the oldest are dropped, in case
+/// nothing drains them.
+pub const MAX_PENDING_DIAGNOSTICS: usize = 1024;
+
+/// A problem the engine recovered from. Diagnostics are collected with
+/// the step they occurred in and read with `GameEngine::drain_diagnostics`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EngineDiagnostic {
+    /// An entity was spawned with id 0. The entity is still spawned.
+    SpawnedZeroId,
+    /// An entity was spawned with the id of an existing entity, replacing it.
+    ReplacedEntity { entity_id: u128 },
+    /// An entity that does not exist was removed.
+    RemovedMissingEntity { entity_id: u128 },
+    /// A system was spawned on an entity that does not exist.
+    SpawnedSystemForMissingEntity { entity_id: u128 },
+    /// A system was removed from an entity that does not exist.
+    RemovedSystemForMissingEntity { entity_id: u128 },
+    /// An entity broke a system constraint and was not spawned.
+    RejectedEntity {
+        entity_id: u128,
+        violation: SystemViolation,
+    },
+    /// A system would break a system constraint of the entity and was not
+    /// attached.
+    RejectedSystem {
+        entity_id: u128,
+        violation: SystemViolation,
+    },
+    /// A world system was spawned with the id of an existing world system,
+    /// replacing it.
+    ReplacedWorldSystem { id: u128 },
+    /// A world system that does not exist was removed.
+    RemovedMissingWorldSystem { id: u128 },
+    /// Events from the past were integrated. The engine rewound to
+    /// `from_step_index` and replayed `steps` steps, the replayed steps
+    /// report their diagnostics again.
+    Rewound { from_step_index: u64, steps: u64 },
+}
+
+impl EngineDiagnostic {
+    /// A stable name for the kind of diagnostic, e.g. for counting.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EngineDiagnostic::SpawnedZeroId => "spawned_zero_id",
+            EngineDiagnostic::ReplacedEntity { .. } => "replaced_entity",
+            EngineDiagnostic::RemovedMissingEntity { .. } => "removed_missing_entity",
+            EngineDiagnostic::SpawnedSystemForMissingEntity { .. } => {
+                "spawned_system_for_missing_entity"
+            }
+            EngineDiagnostic::RemovedSystemForMissingEntity { .. } => {
+                "removed_system_for_missing_entity"
+            }
+            EngineDiagnostic::RejectedEntity { .. } => "rejected_entity",
+            EngineDiagnostic::RejectedSystem { .. } => "rejected_system",
+            EngineDiagnostic::ReplacedWorldSystem { .. } => "replaced_world_system",
+            EngineDiagnostic::RemovedMissingWorldSystem { .. } => "removed_missing_world_system",
+            EngineDiagnostic::Rewound { .. } => "rewound",
+        }
+    }
+}
+
+impl fmt::Display for EngineDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineDiagnostic::SpawnedZeroId => write!(f, "spawned entity with id 0"),
+            EngineDiagnostic::ReplacedEntity { entity_id } => {
+                write!(f, "spawned entity {entity_id} that already existed")
+            }
+            EngineDiagnostic::RemovedMissingEntity { entity_id } => {
+                write!(f, "removed non-existent entity {entity_id}")
+            }
+            EngineDiagnostic::SpawnedSystemForMissingEntity { entity_id } => {
+                write!(f, "spawned system for non-existent entity {entity_id}")
+            }
+            EngineDiagnostic::RemovedSystemForMissingEntity { entity_id } => {
+                write!(f, "removed system for non-existent entity {entity_id}")
+            }
+            EngineDiagnostic::RejectedEntity {
+                entity_id,
+                violation,
+            } => write!(f, "rejected entity {entity_id}: {violation}"),
+            EngineDiagnostic::RejectedSystem {
+                entity_id,
+                violation,
+            } => write!(f, "rejected system for entity {entity_id}: {violation}"),
+            EngineDiagnostic::ReplacedWorldSystem { id } => {
+                write!(f, "spawned world system {id} that already existed")
+            }
+            EngineDiagnostic::RemovedMissingWorldSystem { id } => {
+                write!(f, "removed non-existent world system {id}")
+            }
+            EngineDiagnostic::Rewound {
+                from_step_index,
+                steps,
+            } => write!(
+                f,
+                "rewound to step {from_step_index}, replaying {steps} steps"
+            ),
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::Serialize;
+use serde::ser;
+
+use crate::prelude::*;
+
+/// A leaf value that differs between two entities. `path` is the location
+/// of the value in the serialized entity, e.g. `Player.state.position[0]`.
+/// A side is `None` when the value is not present at all, e.g. an extra
+/// element in a `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub path: String,
+    pub local: Option<String>,
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityDiffKind {
+    OnlyLocal,
+    OnlyRemote,
+    Changed(Vec<FieldDiff>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityDiff {
+    pub id: u128,
+    pub kind: EntityDiffKind,
+}
+
+/// The first step at which two engines disagree and how their entities
+/// differ at that step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesyncReport {
+    pub step_index: u64,
+    /// The last step with a matching hash, if the engines share one.
+    pub last_matching_step: Option<u64>,
+    pub entities: Vec<EntityDiff>,
+}
+
+impl Display for FieldDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let local = self.local.as_deref().unwrap_or("<missing>");
+        let remote = self.remote.as_deref().unwrap_or("<missing>");
+        write!(f, "{}: {local} -> {remote}", self.path)
+    }
+}
+
+impl Display for EntityDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            EntityDiffKind::OnlyLocal => write!(f, "entity {} only exists locally", self.id),
+            EntityDiffKind::OnlyRemote => write!(f, "entity {} only exists remotely", self.id),
+            EntityDiffKind::Changed(fields) => {
+                write!(f, "entity {} differs", self.id)?;
+                for field in fields {
+                    write!(f, "\n  {field}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for DesyncReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "first diverging step {}", self.step_index)?;
+        if let Some(step_index) = self.last_matching_step {
+            write!(f, " (last matching step {step_index})")?;
+        }
+        for entity in &self.entities {
+            write!(f, "\n{entity}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Diff two entity maps, e.g. the same step in a local and remote engine.
+/// Entities are compared by hash first, so only diverging entities are
+/// serialized field by field. Results are ordered by id.
+pub fn diff_entity_maps<G: GameLogic>(
+    local: &PersistentMap<u128, RefPointer<G::Entity>>,
+    remote: &PersistentMap<u128, RefPointer<G::Entity>>,
+) -> Vec<EntityDiff> {
+    let mut out = Vec::new();
+    for (id, entity) in local {
+        match remote.get(id) {
+            Some(remote_entity) => {
+                if RefPointer::ptr_eq(entity, remote_entity)
+                    || GameEngine::<G>::hash_entity(entity)
+                        == GameEngine::<G>::hash_entity(remote_entity)
+                {
+                    continue;
+                }
+                out.push(EntityDiff {
+                    id: *id,
+                    kind: EntityDiffKind::Changed(diff_fields(&**entity, &**remote_entity)),
+                });
+            }
+            None => out.push(EntityDiff {
+                id: *id,
+                kind: EntityDiffKind::OnlyLocal,
+            }),
+        }
+    }
+    for (id, _) in remote {
+        if !local.contains_key(id) {
+            out.push(EntityDiff {
+                id: *id,
+                kind: EntityDiffKind::OnlyRemote,
+            });
+        }
+    }
+    out.sort_by_key(|diff| diff.id);
+    out
+}
+
+/// Compare two values leaf by leaf using their `Serialize` implementations.
+pub fn diff_fields<T: Serialize>(local: &T, remote: &T) -> Vec<FieldDiff> {
+    let local = flatten_fields(local);
+    let mut remote = flatten_fields(remote);
+    let mut out = Vec::new();
+    for (path, local_value) in local {
+        let remote_value = remote.remove(&path);
+        if remote_value.as_ref() != Some(&local_value) {
+            out.push(FieldDiff {
+                path,
+                local: Some(local_value),
+                remote: remote_value,
+            });
+        }
+    }
+    out.extend(remote.into_iter().map(|(path, remote_value)| FieldDiff {
+        path,
+        local: None,
+        remote: Some(remote_value),
+    }));
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    out
+}
+
+/// Flatten a value into a map of path to leaf value.
+pub fn flatten_fields<T: Serialize + ?Sized>(value: &T) -> BTreeMap<String, String> {
+    let mut collector = FieldCollector::default();
+    if let Err(e) = value.serialize(&mut collector) {
+        collector
+            .fields
+            .insert("<error>".to_string(), e.to_string());
+    }
+    collector.fields
+}
+
+#[derive(Debug)]
+struct FieldError(String);
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+impl ser::Error for FieldError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// A serde serializer recording every leaf value along with its path.
+#[derive(Default)]
+struct FieldCollector {
+    path: Vec<String>,
+    fields: BTreeMap<String, String>,
+}
+
+impl FieldCollector {
+    fn leaf(&mut self, value: impl ToString) -> Result<(), FieldError> {
+        let path = self.path.concat();
+        let path = path.strip_prefix('.').unwrap_or(&path).to_string();
+        self.fields.insert(path, value.to_string());
+        Ok(())
+    }
+
+    fn nested<T: Serialize + ?Sized>(
+        &mut self,
+        segment: String,
+        value: &T,
+    ) -> Result<(), FieldError> {
+        self.path.push(segment);
+        let out = value.serialize(&mut *self);
+        self.path.pop();
+        out
+    }
+}
+
+struct Compound<'a> {
+    collector: &'a mut FieldCollector,
+    index: usize,
+    key: Option<String>,
+    /// Whether a variant name was pushed onto the path.
+    is_variant: bool,
+}
+
+impl<'a> Compound<'a> {
+    fn new(collector: &'a mut FieldCollector, variant: Option<&str>) -> Self {
+        if let Some(variant) = variant {
+            collector.path.push(format!(".{variant}"));
+        }
+        Self {
+            collector,
+            index: 0,
+            key: None,
+            is_variant: variant.is_some(),
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FieldError> {
+        let segment = format!("[{}]", self.index);
+        self.index += 1;
+        self.collector.nested(segment, value)
+    }
+
+    fn end(self) -> Result<(), FieldError> {
+        if self.is_variant {
+            self.collector.path.pop();
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut FieldCollector {
+    type Ok = ();
+    type Error = FieldError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_i8(self, v: i8) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_i16(self, v: i16) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_i32(self, v: i32) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_i64(self, v: i64) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_i128(self, v: i128) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_u8(self, v: u8) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_u16(self, v: u16) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_u32(self, v: u32) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_u64(self, v: u64) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_u128(self, v: u128) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_f32(self, v: f32) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_f64(self, v: f64) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_char(self, v: char) -> Result<(), FieldError> {
+        self.leaf(v)
+    }
+    fn serialize_str(self, v: &str) -> Result<(), FieldError> {
+        self.leaf(format!("{v:?}"))
+    }
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), FieldError> {
+        self.leaf(format!("{v:?}"))
+    }
+    fn serialize_none(self) -> Result<(), FieldError> {
+        self.leaf("None")
+    }
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), FieldError> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<(), FieldError> {
+        self.leaf("()")
+    }
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), FieldError> {
+        self.leaf(name)
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), FieldError> {
+        self.leaf(variant)
+    }
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), FieldError> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), FieldError> {
+        self.nested(format!(".{variant}"), value)
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, None))
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, None))
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, None))
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, Some(variant)))
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, None))
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, None))
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, FieldError> {
+        Ok(Compound::new(self, Some(variant)))
+    }
+}
+
+impl ser::SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FieldError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FieldError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FieldError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FieldError> {
+        self.element(value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), FieldError> {
+        // keys are flattened on their own and joined into a single segment
+        let key = flatten_fields(key).into_values().collect::<Vec<_>>();
+        self.key = Some(key.join(","));
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), FieldError> {
+        let key = self.key.take().unwrap_or_default();
+        self.collector.nested(format!("[{key}]"), value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), FieldError> {
+        self.collector.nested(format!(".{key}"), value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = FieldError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), FieldError> {
+        self.collector.nested(format!(".{key}"), value)
+    }
+    fn end(self) -> Result<(), FieldError> {
+        Compound::end(self)
+    }
+}
@@ -8,7 +8,8 @@
 ///
 /// Anatomy of a step:
 ///   - engine events: engine events are process, entity/system addition/removal
-///   - modification: entities modify themselves and schedule entities for creation/removal
+///   - modification: entities modify themselves and schedule entities for creation/removal,
+///     then world systems step
 ///   - game events: game events are processed by the game logic
 ///   - snapshot: the engine state is persisted in memory for rollback
 ///
@@ -20,15 +21,56 @@
 ///   - Per-step, per-entity deterministic RNGs
 ///
 use std::collections::BTreeMap;
-use std::collections::HashMap;
+use std::collections::VecDeque;
 
 use anyhow::Result;
+use bevy_math::IRect;
 use bevy_math::IVec2;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::OnceCell;
+use crate::constraint::check_systems;
+use crate::input::InputHistory;
 use crate::prelude::*;
 
+/// Stages of stepping an entity, the entity's own step then each
+/// `SystemPhase`.
+const ENTITY_STAGES: [Option<SystemPhase>; 5] = [
+    None,
+    Some(SystemPhase::PrePhysics),
+    Some(SystemPhase::Physics),
+    Some(SystemPhase::PostPhysics),
+    Some(SystemPhase::Cleanup),
+];
+
+/// An entity being stepped and its next version, if it changed.
+struct EntityStep<'a, G: GameLogic> {
+    entity: &'a RefPointer<G::Entity>,
+    next_self: Option<G::Entity>,
+    /// Next systems indexed like `entity.systems()`, set once any system
+    /// steps. `None` marks a removed system.
+    next_systems: Option<Vec<Option<RefPointer<G::System>>>>,
+}
+
+impl<'a, G: GameLogic> EntityStep<'a, G> {
+    fn new(entity: &'a RefPointer<G::Entity>) -> Self {
+        Self {
+            entity,
+            next_self: None,
+            next_systems: None,
+        }
+    }
+
+    fn finish(self) -> Option<G::Entity> {
+        let mut next_self = self.next_self?;
+        if let Some(next_systems) = self.next_systems {
+            *next_self.systems_mut() = next_systems.into_iter().flatten().collect();
+        }
+        Some(next_self)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(deserialize = "G: for<'dee> serde::Deserialize<'dee>"))]
 pub struct GameEngine<G: GameLogic> {
@@ -53,20 +95,48 @@ pub struct GameEngine<G: GameLogic> {
     step_index: u64,
 
     /// Entities stored by id. Each entity is behind an Rc allowing for
-    /// cheap copies of unchanged entities.
-    entities: BTreeMap<u128, RefPointer<G::Entity>>,
+    /// cheap copies of unchanged entities. The map itself shares structure
+    /// with the maps in `entities_by_step`.
+    entities: PersistentMap<u128, RefPointer<G::Entity>>,
     /// An empty entity structure to allow references to empty
     /// step data.
-    empty_entities: BTreeMap<u128, RefPointer<G::Entity>>,
-    /// Historical entities for the past trailing_state_len steps.
-    entities_by_step: BTreeMap<u64, BTreeMap<u128, RefPointer<G::Entity>>>,
+    empty_entities: PersistentMap<u128, RefPointer<G::Entity>>,
+    /// Historical entities for the past trailing_state_len steps. Each step
+    /// only stores the entities that changed, the rest is shared.
+    entities_by_step: BTreeMap<u64, PersistentMap<u128, RefPointer<G::Entity>>>,
+    /// Spatial index of `entities`. Built lazily on the first query and
+    /// kept up to date as entities change.
+    #[serde(skip)]
+    spatial_index: OnceCell<SpatialIndex>,
+    /// Hash of each entity in `entities`. Entities that are not cloned
+    /// during a step keep their hash.
+    #[serde(default)]
+    entity_hashes: PersistentMap<u128, blake3::Hash>,
+    /// Historical entity hashes, mirroring `entities_by_step`.
+    #[serde(default)]
+    entity_hashes_by_step: BTreeMap<u64, PersistentMap<u128, blake3::Hash>>,
+
+    /// Entities in contact at the end of the current step.
+    #[serde(default)]
+    contacts: RefPointer<Contacts>,
+    /// Historical contacts, mirroring `entities_by_step`.
+    #[serde(default)]
+    contacts_by_step: BTreeMap<u64, RefPointer<Contacts>>,
+
+    /// World systems stored by id, see `EWorldSystem`.
+    #[serde(default)]
+    world_systems: PersistentMap<u128, RefPointer<G::WorldSystem>>,
+    /// Historical world systems, mirroring `entities_by_step`.
+    #[serde(default)]
+    world_systems_by_step: BTreeMap<u64, PersistentMap<u128, RefPointer<G::WorldSystem>>>,
 
     /// The default input for all entities. Used for cheap `&G::Input` returns.
     default_input: G::Input,
     /// Map of current input to each entity id.
-    inputs: HashMap<u128, G::Input>,
-    /// Map of historical inputs to entity ids, for the past trailing_state_len steps.
-    inputs_by_step: BTreeMap<u64, HashMap<u128, G::Input>>,
+    inputs: PersistentMap<u128, G::Input>,
+    /// Historical inputs, for the past trailing_state_len steps. Only
+    /// the inputs that change are stored for each step.
+    input_history: InputHistory<G::Input>,
 
     /// Engine events that occurred in each step.
     engine_events_by_step: BTreeMap<u64, Vec<EngineEvent<G>>>,
@@ -74,6 +144,15 @@ pub struct GameEngine<G: GameLogic> {
     #[serde(skip)]
     game_events_by_step: BTreeMap<u64, Vec<RefPointer<G::Event>>>,
 
+    /// Diagnostics that have not been drained, with the step they
+    /// occurred in.
+    #[serde(skip)]
+    diagnostics: VecDeque<(u64, EngineDiagnostic)>,
+    /// Diagnostics reported in each step of the history, so replaying a
+    /// step after a rewind doesn't report them again.
+    #[serde(skip)]
+    diagnostics_by_step: BTreeMap<u64, Vec<EngineDiagnostic>>,
+
     /// Used to allow entities to register EngineEvents without
     /// an `&mut GameEngine<G>` reference.
     #[serde(skip, default = "default_engine_events::<G>")]
@@ -88,6 +167,12 @@ pub struct GameEngine<G: GameLogic> {
 
     #[serde(default = "default_trailing_state_len")]
     pub trailing_state_len: u64,
+
+    /// Step entities on multiple threads. The result is identical to
+    /// stepping serially.
+    #[cfg(feature = "parallel")]
+    #[serde(skip, default = "default_parallel")]
+    pub parallel: bool,
 }
 
 fn default_engine_events<G: GameLogic>() -> (
@@ -105,28 +190,44 @@ fn default_trailing_state_len() -> u64 {
     360
 }
 
+#[cfg(feature = "parallel")]
+fn default_parallel() -> bool {
+    true
+}
+
 impl<G: GameLogic> Default for GameEngine<G> {
     fn default() -> Self {
-        let mut inputs_by_step = BTreeMap::default();
-        inputs_by_step.insert(0, HashMap::default());
         let mut entities_by_step = BTreeMap::default();
-        entities_by_step.insert(0, BTreeMap::default());
+        entities_by_step.insert(0, PersistentMap::default());
+        let mut world_systems_by_step = BTreeMap::default();
+        world_systems_by_step.insert(0, PersistentMap::default());
         Self {
             id: 0,
             id_counter: (0u64, 0u64),
             size: IVec2::new(1000, 1000), // initialize a 1000x1000 2d space for entities
             step_index: 0,
-            entities: BTreeMap::default(),
-            empty_entities: BTreeMap::default(),
+            entities: PersistentMap::default(),
+            empty_entities: PersistentMap::default(),
             entities_by_step,
+            spatial_index: OnceCell::new(),
+            entity_hashes: PersistentMap::default(),
+            entity_hashes_by_step: BTreeMap::default(),
+            contacts: RefPointer::default(),
+            contacts_by_step: BTreeMap::default(),
+            world_systems: PersistentMap::default(),
+            world_systems_by_step,
             default_input: G::Input::default(),
-            inputs: HashMap::new(),
-            inputs_by_step,
+            inputs: PersistentMap::default(),
+            input_history: InputHistory::default(),
             game_events_by_step: BTreeMap::default(),
             engine_events_by_step: BTreeMap::default(),
+            diagnostics: VecDeque::default(),
+            diagnostics_by_step: BTreeMap::default(),
             game_events: flume::unbounded(),
             engine_events: flume::unbounded(),
             trailing_state_len: default_trailing_state_len(),
+            #[cfg(feature = "parallel")]
+            parallel: default_parallel(),
         }
     }
 }
@@ -167,6 +268,7 @@ impl<G: GameLogic> GameEngine<G> {
 
     pub fn remove_entity_immediate(&mut self, entity_id: &u128) {
         self.entities.remove(entity_id);
+        self.entity_changed(entity_id);
     }
 
     pub fn remove_entity(&self, entity_id: u128) {
@@ -201,12 +303,110 @@ impl<G: GameLogic> GameEngine<G> {
         );
     }
 
+    /// Register a world system to start stepping in the next step. `id`
+    /// should come from `generate_id`.
+    pub fn spawn_world_system(&self, id: u128, system: G::WorldSystem) {
+        self.register_event(
+            Some(self.step_index),
+            EngineEvent::SpawnWorldSystem {
+                id,
+                system_ptr: RefPointer::new(system),
+                is_non_determinism: false,
+            },
+        );
+    }
+
+    pub fn remove_world_system(&self, id: u128) {
+        self.register_event(
+            Some(self.step_index),
+            EngineEvent::RemoveWorldSystem {
+                id,
+                is_non_determinism: false,
+            },
+        );
+    }
+
+    /// Entities in contact in the current step.
+    pub fn contacts(&self) -> &Contacts {
+        &self.contacts
+    }
+
+    /// World systems in the current step, by id.
+    pub fn world_systems(&self) -> &PersistentMap<u128, RefPointer<G::WorldSystem>> {
+        &self.world_systems
+    }
+
+    /// Record a diagnostic for the current step, unless it was reported
+    /// for the step before a rewind.
+    fn report(&mut self, diagnostic: EngineDiagnostic) {
+        if self.trailing_state_len != 0 {
+            let reported = self.diagnostics_by_step.entry(self.step_index).or_default();
+            if reported.contains(&diagnostic) {
+                return;
+            }
+            reported.push(diagnostic.clone());
+        }
+        self.report_at(self.step_index, diagnostic);
+    }
+
+    fn report_at(&mut self, step_index: u64, diagnostic: EngineDiagnostic) {
+        if self.diagnostics.len() == MAX_PENDING_DIAGNOSTICS {
+            self.diagnostics.pop_front();
+        }
+        self.diagnostics.push_back((step_index, diagnostic));
+    }
+
+    /// Diagnostics that have not been drained, in the order they were
+    /// reported.
+    pub fn diagnostics(&self) -> impl Iterator<Item = &(u64, EngineDiagnostic)> {
+        self.diagnostics.iter()
+    }
+
+    /// Take all pending diagnostics, in the order they were reported.
+    pub fn drain_diagnostics(&mut self) -> Vec<(u64, EngineDiagnostic)> {
+        self.diagnostics.drain(..).collect()
+    }
+
+    /// A copy of the engine to step speculatively, e.g. to predict ahead
+    /// of a confirmed engine on the client. History is shared with this
+    /// engine through `RefPointer`, so forking is cheap.
+    ///
+    /// The fork has its own event channels: events pending in this engine
+    /// are copied to the fork, and events registered later on either
+    /// engine are not seen by the other. Diagnostics are not copied.
+    pub fn fork(&self) -> Self {
+        let mut out = self.clone();
+        out.engine_events = flume::unbounded();
+        out.game_events = flume::unbounded();
+        out.diagnostics = VecDeque::default();
+        // the channels are shared with the clone, drain and resend to both
+        for event in self.engine_events.1.drain() {
+            out.engine_events.0.send(event.clone()).unwrap();
+            self.engine_events.0.send(event).unwrap();
+        }
+        for event in self.game_events.1.drain() {
+            out.game_events.0.send(event.clone()).unwrap();
+            self.game_events.0.send(event).unwrap();
+        }
+        out
+    }
+
     pub fn register_event(&self, step_index: Option<u64>, event: EngineEvent<G>) {
         let step_index = step_index.unwrap_or(self.step_index);
-        self.engine_events.0.send((step_index, event)).unwrap();
+        #[cfg(feature = "parallel")]
+        let Some(event) = crate::parallel::capture_engine_event((step_index, event)) else {
+            return;
+        };
+        #[cfg(not(feature = "parallel"))]
+        let event = (step_index, event);
+        self.engine_events.0.send(event).unwrap();
     }
 
     pub fn register_game_event(&self, event: G::Event) {
+        #[cfg(feature = "parallel")]
+        let Some(event) = crate::parallel::capture_game_event::<G>(event) else {
+            return;
+        };
         self.game_events.0.send(event).unwrap();
     }
 
@@ -236,39 +436,196 @@ impl<G: GameLogic> GameEngine<G> {
             .collect::<Vec<_>>()
     }
 
+    /// The spatial index for the current step.
+    pub fn spatial_index(&self) -> &SpatialIndex {
+        self.spatial_index
+            .get_or_init(|| SpatialIndex::from_entities::<G>(&self.entities))
+    }
+
+    /// Entities in the current step intersecting `rect`, ordered by id.
+    pub fn entities_in_rect(&self, rect: &IRect) -> Vec<&RefPointer<G::Entity>> {
+        self.spatial_index()
+            .query(rect)
+            .iter()
+            .filter_map(|id| self.entities.get(id))
+            .collect::<Vec<_>>()
+    }
+
+    /// Entities of type `T` in the current step intersecting `rect`, ordered by id.
+    pub fn entities_by_type_in_rect<T: SEEntity<G> + 'static>(&self, rect: &IRect) -> Vec<&T> {
+        self.entities_in_rect(rect)
+            .into_iter()
+            .filter_map(|entity| entity.extract_ref::<T>())
+            .collect::<Vec<_>>()
+    }
+
+    /// Update the spatial index and entity hash after `entities`
+    /// has changed for `id`.
+    fn entity_changed(&mut self, id: &u128) {
+        match self.entities.get(id) {
+            Some(entity) => {
+                self.entity_hashes.insert(*id, Self::hash_entity(entity));
+                if let Some(index) = self.spatial_index.get_mut() {
+                    index.insert(*id, SpatialIndex::entity_rect::<G>(entity));
+                }
+            }
+            None => {
+                self.entity_hashes.remove(id);
+                if let Some(index) = self.spatial_index.get_mut() {
+                    index.remove(id);
+                }
+            }
+        }
+    }
+
     pub fn input_for_entity(&self, id: &u128) -> &G::Input {
         self.inputs.get(id).unwrap_or(&self.default_input)
     }
 
-    /// A step is considered complete at the _end_ of this function
-    pub fn step(&mut self) -> Vec<RefPointer<G::Event>> {
-        // Execute the modification phase of the step
-        // When an entity is stepped we get a mutable next version
-        // as a clone of the current version, then apply all
-        // systems. Once this is complete it is put in a RefPointer
-        // and stored.
-        let mut next_entities = BTreeMap::default();
-        for (id, entity) in &self.entities {
-            let mut next_self_maybe = None;
+    /// The input of an entity at the end of `step_index`, if the step is
+    /// in the engine history.
+    pub fn input_for_entity_at_step(&self, step_index: &u64, id: &u128) -> Option<&G::Input> {
+        self.input_history
+            .input_at(step_index, id)
+            .map(|input| input.unwrap_or(&self.default_input))
+    }
+
+    /// Step every entity and then its systems phase by phase, see
+    /// `SystemPhase`. Returns the next version of each entity that
+    /// changed, entities that don't change are not cloned.
+    fn step_entities(&self) -> Vec<(u128, G::Entity)> {
+        let mut steps = self
+            .entities
+            .iter()
+            .map(|(id, entity)| (*id, EntityStep::new(entity)))
+            .collect::<Vec<_>>();
+        #[cfg(feature = "parallel")]
+        if self.parallel {
+            self.step_entity_stages_parallel(&mut steps);
+        } else {
+            self.step_entity_stages(&mut steps);
+        }
+        #[cfg(not(feature = "parallel"))]
+        self.step_entity_stages(&mut steps);
+        steps
+            .into_iter()
+            .filter_map(|(id, step)| Some((id, step.finish()?)))
+            .collect()
+    }
+
+    /// Every entity completes a stage before any entity starts the next.
+    fn step_entity_stages(&self, steps: &mut [(u128, EntityStep<G>)]) {
+        for stage in ENTITY_STAGES {
+            for (_, step) in steps.iter_mut() {
+                self.step_entity_stage(stage, step);
+            }
+        }
+    }
+
+    /// Step each entity through every stage on a rayon thread. Entities
+    /// only read the previous step so the result is the same as
+    /// `step_entity_stages`. Events are captured and sent afterward in the
+    /// order `step_entity_stages` would have sent them.
+    #[cfg(feature = "parallel")]
+    fn step_entity_stages_parallel(&self, steps: &mut [(u128, EntityStep<G>)]) {
+        use rayon::prelude::*;
+
+        let mut captured = steps
+            .par_iter_mut()
+            .map(|(_, step)| {
+                ENTITY_STAGES.map(|stage| {
+                    crate::parallel::capture_events::<G>(|| self.step_entity_stage(stage, step))
+                })
+            })
+            .collect::<Vec<_>>();
+        for stage in 0..ENTITY_STAGES.len() {
+            for events in &mut captured {
+                let events = std::mem::take(&mut events[stage]);
+                for event in events.engine_events {
+                    self.engine_events.0.send(event).unwrap();
+                }
+                for event in events.game_events {
+                    self.game_events.0.send(event).unwrap();
+                }
+            }
+        }
+    }
+
+    fn step_entity_stage(&self, stage: Option<SystemPhase>, step: &mut EntityStep<G>) {
+        let entity = step.entity;
+        let Some(phase) = stage else {
             if entity.prestep(self) {
                 let mut next_self = (**entity).clone();
                 entity.step(self, &mut next_self);
-                next_self_maybe = Some(next_self);
+                step.next_self = Some(next_self);
+            }
+            return;
+        };
+        let mut systems = entity
+            .systems()
+            .iter()
+            .enumerate()
+            .filter(|(_, system)| system.phase() == phase)
+            .collect::<Vec<_>>();
+        // stable for equal priority, oldest systems first
+        systems.sort_by_key(|(_, system)| system.priority());
+        for (i, system) in systems {
+            if !system.prestep(self, entity) {
+                continue;
             }
-            entity.step_systems(self, &mut next_self_maybe);
-            // insert the next_self, if it exists
-            // otherwise copy the existingRefPointer
-            let next_self_ptr = if let Some(next_self) = next_self_maybe {
-                RefPointer::from(next_self)
+            // the system has requested a clone, we need to clone the parent entity
+            // as well
+            let next_self = step.next_self.get_or_insert_with(|| (**entity).clone());
+            // systems determine whether a clone is necessary
+            let next_system = system.step(self, entity, next_self).map(RefPointer::from);
+            step.next_systems
+                .get_or_insert_with(|| entity.systems().iter().cloned().map(Some).collect())[i] =
+                next_system;
+        }
+    }
+
+    /// Step every world system in id order, returning the next world
+    /// systems.
+    fn step_world_systems(&self) -> PersistentMap<u128, RefPointer<G::WorldSystem>> {
+        let mut next_world_systems = self.world_systems.clone();
+        for (id, system) in &self.world_systems {
+            if !system.prestep(self, id) {
+                continue;
+            }
+            if let Some(next_system) = system.step(self, id) {
+                next_world_systems.insert(*id, RefPointer::new(next_system));
             } else {
-                entity.clone()
-            };
-            if next_entities.insert(*id, next_self_ptr).is_some() {
-                println!("WARNING: stepped an entity that was not previously present!");
+                next_world_systems.remove(id);
             }
         }
+        next_world_systems
+    }
+
+    /// A step is considered complete at the _end_ of this function
+    pub fn step(&mut self) -> Vec<RefPointer<G::Event>> {
+        // Execute the modification phase of the step
+        // When an entity is stepped we get a mutable next version
+        // as a clone of the current version, then apply all
+        // systems. Once this is complete it is put in a RefPointer
+        // and stored. Entities that don't change keep their existing
+        // RefPointer and map structure.
+        let mut next_entities = self.entities.clone();
+        // ids of entities that were cloned this step
+        let mut changed_ids = Vec::new();
+        for (id, next_self) in self.step_entities() {
+            changed_ids.push(id);
+            next_entities.insert(id, RefPointer::from(next_self));
+        }
+
+        // world systems read the same state as entity systems, before the
+        // stepped entities are applied
+        let next_world_systems = self.step_world_systems();
 
         self.entities = next_entities;
+        for id in changed_ids.drain(..) {
+            self.entity_changed(&id);
+        }
+        self.world_systems = next_world_systems;
 
         // our entities are stepped, now we have discrete
         // engine events to apply to self.entities
@@ -284,6 +641,10 @@ impl<G: GameLogic> GameEngine<G> {
                 .push(event);
         }
 
+        let mut diagnostics = Vec::new();
+        let mut input_changes = Vec::new();
+        // entities spawned and removed this step, in event order
+        let mut lifecycle = Vec::new();
         // iterate over all events for the current step
         for event in self
             .engine_events_by_step
@@ -292,23 +653,35 @@ impl<G: GameLogic> GameEngine<G> {
         {
             match event {
                 EngineEvent::SpawnEntity { entity, .. } => {
+                    if let Err(violation) = check_systems::<G>(entity) {
+                        diagnostics.push(EngineDiagnostic::RejectedEntity {
+                            entity_id: entity.id(),
+                            violation,
+                        });
+                        continue;
+                    }
                     if entity.id() == 0 {
-                        println!("WARNING: refusing to spawn entity with id 0");
+                        diagnostics.push(EngineDiagnostic::SpawnedZeroId);
                     }
-                    if let Some(e) = self.entities.insert(entity.id(), entity.clone()) {
-                        println!("WARNING: inserting entity that already existed! {:?}", e);
-                        // if &e == entity {
-                        //     println!("entities are equal");
-                        // }
-                        println!("new: {:?}", entity);
+                    changed_ids.push(entity.id());
+                    lifecycle.push((true, entity.clone()));
+                    if self.entities.insert(entity.id(), entity.clone()).is_some() {
+                        diagnostics.push(EngineDiagnostic::ReplacedEntity {
+                            entity_id: entity.id(),
+                        });
                     }
                 }
                 EngineEvent::RemoveEntity {
                     entity_id,
                     is_non_determinism: _,
                 } => {
-                    if let None = self.entities.remove(&entity_id) {
-                        println!("WARNING: attempting to remove non-existent entity");
+                    changed_ids.push(*entity_id);
+                    if let Some(entity) = self.entities.remove(entity_id) {
+                        lifecycle.push((false, entity));
+                    } else {
+                        diagnostics.push(EngineDiagnostic::RemovedMissingEntity {
+                            entity_id: *entity_id,
+                        });
                     }
                 }
                 EngineEvent::Input {
@@ -317,6 +690,7 @@ impl<G: GameLogic> GameEngine<G> {
                     is_non_determinism: _,
                 } => {
                     self.inputs.insert(*entity_id, input.clone());
+                    input_changes.push((*entity_id, input.clone()));
                 }
                 EngineEvent::SpawnSystem {
                     entity_id,
@@ -326,13 +700,23 @@ impl<G: GameLogic> GameEngine<G> {
                     if let Some(entity_ptr) = self.entities.get(entity_id) {
                         let mut entity = (**entity_ptr).clone();
                         entity.systems_mut().push(system_ptr.clone());
+                        if let Err(violation) = check_systems::<G>(&entity) {
+                            diagnostics.push(EngineDiagnostic::RejectedSystem {
+                                entity_id: *entity_id,
+                                violation,
+                            });
+                            continue;
+                        }
+                        changed_ids.push(*entity_id);
                         assert!(
                             self.entities
                                 .insert(entity.id(), RefPointer::new(entity))
                                 .is_some()
                         );
                     } else {
-                        println!("WARNING: attempting to spawn system for non-existent entity");
+                        diagnostics.push(EngineDiagnostic::SpawnedSystemForMissingEntity {
+                            entity_id: *entity_id,
+                        });
                     }
                 }
                 EngineEvent::RemoveSystem {
@@ -341,6 +725,7 @@ impl<G: GameLogic> GameEngine<G> {
                     ..
                 } => {
                     if let Some(entity_ptr) = self.entities.get(entity_id) {
+                        changed_ids.push(*entity_id);
                         let mut entity = (**entity_ptr).clone();
                         entity
                             .systems_mut()
@@ -351,23 +736,57 @@ impl<G: GameLogic> GameEngine<G> {
                                 .is_some()
                         );
                     } else {
-                        println!("WARNING: attempting to remove system for non-existent entity");
+                        diagnostics.push(EngineDiagnostic::RemovedSystemForMissingEntity {
+                            entity_id: *entity_id,
+                        });
+                    }
+                }
+                EngineEvent::SpawnWorldSystem { id, system_ptr, .. } => {
+                    if self.world_systems.insert(*id, system_ptr.clone()).is_some() {
+                        diagnostics.push(EngineDiagnostic::ReplacedWorldSystem { id: *id });
+                    }
+                }
+                EngineEvent::RemoveWorldSystem { id, .. } => {
+                    if self.world_systems.remove(id).is_none() {
+                        diagnostics.push(EngineDiagnostic::RemovedMissingWorldSystem { id: *id });
                     }
                 }
             }
         }
 
+        for id in changed_ids {
+            self.entity_changed(&id);
+        }
+        for diagnostic in diagnostics {
+            self.report(diagnostic);
+        }
+
         // record step change
         // this changes the behavior of e.g. `GameEngine<G>::entity_by_id`
         self.step_index += 1;
         self.restart_id_counter();
+        let contacts = Contacts::compute::<G>(&self.entities, self.spatial_index(), &self.contacts);
+        self.contacts = RefPointer::new(contacts);
+
+        for (spawned, entity) in lifecycle {
+            if spawned {
+                entity.on_spawn(self);
+            } else {
+                entity.on_despawn(self);
+            }
+        }
 
         // record state for rewind
         if self.trailing_state_len != 0 {
             self.entities_by_step
                 .insert(self.step_index, self.entities.clone());
-            self.inputs_by_step
-                .insert(self.step_index, self.inputs.clone());
+            self.entity_hashes_by_step
+                .insert(self.step_index, self.entity_hashes.clone());
+            self.input_history.record(self.step_index, input_changes);
+            self.world_systems_by_step
+                .insert(self.step_index, self.world_systems.clone());
+            self.contacts_by_step
+                .insert(self.step_index, self.contacts.clone());
         }
 
         let game_events = self
@@ -390,10 +809,16 @@ impl<G: GameLogic> GameEngine<G> {
         if self.trailing_state_len != 0 && self.step_index >= self.trailing_state_len {
             let step_to_remove = self.step_index - self.trailing_state_len;
             self.entities_by_step.retain(|k, _v| k > &step_to_remove);
+            self.entity_hashes_by_step
+                .retain(|k, _v| k > &step_to_remove);
             self.engine_events_by_step
                 .retain(|k, _v| k > &step_to_remove);
             self.game_events_by_step.retain(|k, _v| k > &step_to_remove);
-            self.inputs_by_step.remove(&step_to_remove);
+            self.input_history.forget_before(step_to_remove + 1);
+            self.world_systems_by_step
+                .retain(|k, _v| k > &step_to_remove);
+            self.contacts_by_step.retain(|k, _v| k > &step_to_remove);
+            self.diagnostics_by_step.retain(|k, _v| k > &step_to_remove);
         }
 
         // for exfil
@@ -417,19 +842,172 @@ impl<G: GameLogic> GameEngine<G> {
         }
     }
 
+    /// Capture everything needed to continue this engine deterministically.
+    /// `game_data_hash` identifies the static data the game logic depends on
+    /// and must match when restoring.
+    pub fn snapshot(&self, game_data_hash: blake3::Hash) -> EngineSnapshot<G> {
+        // channels can't be inspected, so drain them and send everything back
+        let pending_engine_events = self.engine_events.1.drain().collect::<Vec<_>>();
+        for event in &pending_engine_events {
+            self.engine_events.0.send(event.clone()).unwrap();
+        }
+        let pending_game_events = self.game_events.1.drain().collect::<Vec<_>>();
+        for event in &pending_game_events {
+            self.game_events.0.send(event.clone()).unwrap();
+        }
+        EngineSnapshot {
+            header: SnapshotHeader {
+                format_version: SNAPSHOT_FORMAT_VERSION,
+                engine_id: self.id,
+                step_index: self.step_index,
+                game_data_hash,
+            },
+            engine: self.clone(),
+            game_events_by_step: self.game_events_by_step.clone(),
+            pending_engine_events,
+            pending_game_events,
+        }
+    }
+
+    /// Restore an engine from a snapshot taken with the same game data.
+    pub fn from_snapshot(
+        snapshot: EngineSnapshot<G>,
+        game_data_hash: &blake3::Hash,
+    ) -> Result<Self> {
+        let header = snapshot.header;
+        if header.format_version != SNAPSHOT_FORMAT_VERSION {
+            anyhow::bail!(
+                "unsupported snapshot format version {}, expected {SNAPSHOT_FORMAT_VERSION}",
+                header.format_version
+            );
+        }
+        if &header.game_data_hash != game_data_hash {
+            anyhow::bail!(
+                "snapshot game data hash {} does not match {game_data_hash}",
+                header.game_data_hash
+            );
+        }
+        let mut out = snapshot.engine;
+        if header.engine_id != out.id || header.step_index != out.step_index {
+            anyhow::bail!("snapshot header does not match engine state");
+        }
+        // an in memory snapshot shares channels with the source engine
+        out.engine_events = flume::unbounded();
+        out.game_events = flume::unbounded();
+        out.game_events_by_step = snapshot.game_events_by_step;
+        for (step_index, event) in snapshot.pending_engine_events {
+            out.register_event(Some(step_index), event);
+        }
+        for event in snapshot.pending_game_events {
+            out.register_game_event(event);
+        }
+        Ok(out)
+    }
+
     pub fn step_hash(&self, step_index: &u64) -> Result<blake3::Hash> {
         if step_index == &0 {
             println!(
                 "WARNING: Calculating a hash for step 0 is nonsensical, there cannot be any entities"
             );
         }
-        // hash of all entities
-        if let Some(entities) = self.entities_by_step.get(step_index) {
-            let serialized = bincode::serialize(entities)?;
-            Ok(blake3::hash(&serialized))
+        // combine the entity hashes in id order
+        let mut hasher = blake3::Hasher::new();
+        for (id, hash) in self.entity_hashes_at_step(step_index)? {
+            hasher.update(&id.to_le_bytes());
+            hasher.update(hash.as_bytes());
+        }
+        // then world systems, an engine without world systems hashes the same
+        // as the entities alone
+        let world_systems = match self.world_systems_by_step.get(step_index) {
+            Some(world_systems) => Some(world_systems),
+            None if step_index == &self.step_index => Some(&self.world_systems),
+            None => None,
+        };
+        if let Some(world_systems) = world_systems {
+            for (id, system) in world_systems.iter() {
+                let serialized = bincode::serialize(&**system)
+                    .expect("failed to serialize world system for hashing");
+                hasher.update(&id.to_le_bytes());
+                hasher.update(blake3::hash(&serialized).as_bytes());
+            }
+        }
+        Ok(hasher.finalize())
+    }
+
+    /// Hash a single entity. Entity hashes are combined into
+    /// a step hash by `step_hash`.
+    pub fn hash_entity(entity: &G::Entity) -> blake3::Hash {
+        let serialized =
+            bincode::serialize(entity).expect("failed to serialize entity for hashing");
+        blake3::hash(&serialized)
+    }
+
+    /// The hash of each entity present at `step_index`, keyed by id. Cached
+    /// hashes are used where available. The current step is always known,
+    /// even when `trailing_state_len` is 0.
+    pub fn entity_hashes_at_step(&self, step_index: &u64) -> Result<BTreeMap<u128, blake3::Hash>> {
+        let (entities, cached) = if let Some(entities) = self.entities_by_step.get(step_index) {
+            (entities, self.entity_hashes_by_step.get(step_index))
+        } else if step_index == &self.step_index {
+            (&self.entities, Some(&self.entity_hashes))
         } else {
             anyhow::bail!("error calculating engine.step_hash, {step_index} not known to engine");
+        };
+        Ok(entities
+            .iter()
+            .map(|(id, entity)| {
+                let hash = cached
+                    .and_then(|hashes| hashes.get(id))
+                    .copied()
+                    .unwrap_or_else(|| Self::hash_entity(entity));
+                (*id, hash)
+            })
+            .collect())
+    }
+
+    /// Ids of entities whose hash at `step_index` differs between `self`
+    /// and `other`. Entities present in only one engine are included.
+    pub fn diverging_entity_ids(&self, other: &Self, step_index: &u64) -> Result<Vec<u128>> {
+        let hashes = self.entity_hashes_at_step(step_index)?;
+        let other_hashes = other.entity_hashes_at_step(step_index)?;
+        let mut out = hashes
+            .iter()
+            .filter(|(id, hash)| other_hashes.get(id) != Some(hash))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        out.extend(
+            other_hashes
+                .keys()
+                .filter(|id| !hashes.contains_key(id))
+                .copied(),
+        );
+        out.sort();
+        Ok(out)
+    }
+
+    /// Find the first step known to both engines where the step hashes
+    /// differ, and diff the entities at that step. Every shared step is
+    /// compared rather than bisecting, a divergence can disappear again
+    /// when the diverging entities are removed.
+    ///
+    /// To compare against a snapshot use `EngineSnapshot::engine`.
+    pub fn find_desync(&self, other: &Self) -> Result<Option<DesyncReport>> {
+        let mut last_matching_step = None;
+        for (step_index, entities) in self.entities_by_step.range(1..) {
+            let Some(other_entities) = other.entities_by_step.get(step_index) else {
+                continue;
+            };
+            if self.step_hash(step_index)? == other.step_hash(step_index)? {
+                last_matching_step = Some(*step_index);
+                continue;
+            }
+            return Ok(Some(DesyncReport {
+                step_index: *step_index,
+                last_matching_step,
+                entities: diff_entity_maps::<G>(entities, other_entities),
+            }));
         }
+        Ok(None)
     }
 
     pub fn game_events(&self, from_step: u64, to_step: u64) -> Vec<RefPointer<G::Event>> {
@@ -448,7 +1026,7 @@ impl<G: GameLogic> GameEngine<G> {
     /// is_non_determinism events occur independently on the engine state. e.g. a player logging on
     pub fn engine_at_step(&self, target_step_index: &u64, rewindable: bool) -> Result<Self> {
         if let Some(entities) = self.entities_by_step.get(target_step_index)
-            && let Some(inputs) = self.inputs_by_step.get(target_step_index)
+            && let Some(inputs) = self.input_history.inputs_at(target_step_index)
         {
             let mut out = Self::default();
 
@@ -475,13 +1053,25 @@ impl<G: GameLogic> GameEngine<G> {
             );
             out.entities_by_step
                 .insert(*target_step_index, entities.clone());
-            out.inputs_by_step.insert(
-                *target_step_index,
-                self.inputs_by_step
-                    .get(target_step_index)
-                    .cloned()
-                    .unwrap_or_default(),
-            );
+            if let Some(hashes) = self.entity_hashes_by_step.get(target_step_index) {
+                out.entity_hashes_by_step
+                    .insert(*target_step_index, hashes.clone());
+            }
+            out.input_history = InputHistory::new(*target_step_index, inputs.clone());
+            let world_systems = self
+                .world_systems_by_step
+                .get(target_step_index)
+                .cloned()
+                .unwrap_or_default();
+            out.world_systems_by_step
+                .insert(*target_step_index, world_systems.clone());
+            let contacts = self
+                .contacts_by_step
+                .get(target_step_index)
+                .cloned()
+                .unwrap_or_default();
+            out.contacts_by_step
+                .insert(*target_step_index, contacts.clone());
 
             if rewindable {
                 // engine events are emitted when the step occurs
@@ -502,16 +1092,38 @@ impl<G: GameLogic> GameEngine<G> {
                         .range(..target_step_index)
                         .map(|(si, data)| (*si, data.clone())),
                 );
-                out.inputs_by_step.extend(
-                    self.inputs_by_step
+                out.entity_hashes_by_step.extend(
+                    self.entity_hashes_by_step
+                        .range(..target_step_index)
+                        .map(|(si, data)| (*si, data.clone())),
+                );
+                out.input_history = self.input_history.clone();
+                out.input_history.forget_after(*target_step_index);
+                out.world_systems_by_step.extend(
+                    self.world_systems_by_step
+                        .range(..target_step_index)
+                        .map(|(k, v)| (*k, v.clone())),
+                );
+                out.contacts_by_step.extend(
+                    self.contacts_by_step
                         .range(..target_step_index)
                         .map(|(k, v)| (*k, v.clone())),
                 );
             }
             out.id = self.id;
             out.size = self.size.clone();
+            #[cfg(feature = "parallel")]
+            {
+                out.parallel = self.parallel;
+            }
             out.entities = entities.clone();
-            out.inputs = inputs.clone();
+            out.entity_hashes = out
+                .entity_hashes_at_step(target_step_index)?
+                .into_iter()
+                .collect();
+            out.inputs = inputs;
+            out.world_systems = world_systems;
+            out.contacts = contacts;
             out.step_index = *target_step_index;
             out.restart_id_counter();
 
@@ -528,51 +1140,137 @@ impl<G: GameLogic> GameEngine<G> {
         }
     }
 
-    pub fn integrate_event(&mut self, step_index: u64, event: EngineEvent<G>) {
+    pub fn integrate_event(
+        &mut self,
+        step_index: u64,
+        event: EngineEvent<G>,
+    ) -> Result<(), IntegrateError> {
         let mut btree: BTreeMap<u64, Vec<EngineEvent<G>>> = BTreeMap::new();
         btree.entry(step_index).or_default().push(event);
-        self.integrate_events(btree);
+        self.integrate_events(btree)
     }
 
-    pub fn integrate_events(&mut self, events: BTreeMap<u64, Vec<EngineEvent<G>>>) {
-        if events.is_empty() {
-            return;
-        }
-        // go to the step before the first event
-        let from_step_index = *events.first_key_value().unwrap().0;
+    /// Register events, rewinding and replaying the engine in place if any
+    /// of them are in the past. Fails without changing the engine if the
+    /// events are older than the kept history.
+    pub fn integrate_events(
+        &mut self,
+        events: BTreeMap<u64, Vec<EngineEvent<G>>>,
+    ) -> Result<(), IntegrateError> {
+        let Some(from_step_index) = events.first_key_value().map(|(k, _)| *k) else {
+            return Ok(());
+        };
         if from_step_index >= self.step_index {
             for (step_index, events) in events {
                 for event in events {
-                    println!("integrating event at step {step_index}");
                     self.register_event(Some(step_index), event);
                 }
             }
-        } else {
-            println!(
-                "at step: {} replaying {} steps",
-                self.step_index,
-                self.step_index - from_step_index
-            );
-            // we receive an event from the past, rewind and replay
-            if let Ok(mut past_engine) = self.engine_at_step(&from_step_index, true) {
-                past_engine.integrate_events(events);
-                past_engine.step_to(&self.step_index);
-
-                // TODO: wtf is this manual copy
-                self.game_events_by_step = past_engine.game_events_by_step;
-                self.entities = past_engine.entities;
-                self.entities_by_step = past_engine.entities_by_step;
-                self.engine_events_by_step = past_engine.engine_events_by_step;
-                self.inputs_by_step = past_engine.inputs_by_step;
-                self.inputs = past_engine.inputs;
-                self.id_counter = past_engine.id_counter;
-            } else {
-                panic!("failed to generate past engine");
+            return Ok(());
+        }
+        // we receive an event from the past, rewind and replay
+        let to_step_index = self.step_index;
+        let pending = self.rewind_to(&from_step_index)?;
+        self.report_at(
+            to_step_index,
+            EngineDiagnostic::Rewound {
+                from_step_index,
+                steps: to_step_index - from_step_index,
+            },
+        );
+        for (step_index, events) in events {
+            for event in events {
+                self.register_event(Some(step_index), event);
             }
         }
+        self.step_to(&to_step_index);
+        for event in pending {
+            self.engine_events.0.send(event).unwrap();
+        }
+        Ok(())
     }
 
-    pub fn entities_at_step(&self, step_index: &u64) -> &BTreeMap<u128, RefPointer<G::Entity>> {
+    /// Return the engine to the end of `step_index`, as `engine_at_step`
+    /// would, dropping the history after it. Returns the pending
+    /// `is_non_determinism` engine events, other pending events are
+    /// recreated when the engine is stepped again.
+    fn rewind_to(
+        &mut self,
+        step_index: &u64,
+    ) -> Result<Vec<(u64, EngineEvent<G>)>, IntegrateError> {
+        let (Some(entities), Some(inputs)) = (
+            self.entities_by_step.get(step_index).cloned(),
+            self.input_history.inputs_at(step_index),
+        ) else {
+            return Err(IntegrateError::TooLate {
+                step_index: *step_index,
+                oldest_step_index: self
+                    .entities_by_step
+                    .keys()
+                    .find(|k| self.input_history.contains(k))
+                    .copied()
+                    .unwrap_or(self.step_index),
+            });
+        };
+        let entity_hashes = self
+            .entity_hashes_at_step(step_index)
+            .expect("entities exist at the rewind step")
+            .into_iter()
+            .collect();
+
+        // everything after the step is recomputed by stepping
+        let after = step_index + 1;
+        self.entities_by_step.split_off(&after);
+        self.entity_hashes_by_step.split_off(&after);
+        self.input_history.forget_after(*step_index);
+        self.world_systems_by_step.split_off(&after);
+        self.contacts_by_step.split_off(&after);
+        self.game_events_by_step.split_off(&after);
+        // diagnostics_by_step is kept, the replayed steps only report new
+        // diagnostics
+        // engine events are emitted when the step occurs, so only keep the
+        // events that are independent of the engine state
+        for (_, events) in self.engine_events_by_step.range_mut(step_index..) {
+            events.retain(|event| event.is_non_determinism());
+        }
+        let pending = self
+            .engine_events
+            .1
+            .drain()
+            .filter(|(_, event)| event.is_non_determinism())
+            .collect();
+        self.game_events.1.drain();
+
+        self.entities = entities;
+        self.entity_hashes = entity_hashes;
+        self.inputs = inputs;
+        self.world_systems = self
+            .world_systems_by_step
+            .get(step_index)
+            .cloned()
+            .unwrap_or_default();
+        self.contacts = self
+            .contacts_by_step
+            .get(step_index)
+            .cloned()
+            .unwrap_or_default();
+        self.spatial_index = OnceCell::new();
+        self.step_index = *step_index;
+        self.restart_id_counter();
+
+        let game_events = self
+            .game_events_by_step
+            .get(step_index)
+            .cloned()
+            .unwrap_or_default();
+        G::handle_game_events(self, &game_events);
+        Ok(pending)
+    }
+
+    pub fn entities_at_step(
+        &self,
+        step_index: &u64,
+    ) -> &PersistentMap<u128, RefPointer<G::Entity>> {
         if step_index == &0 {
             return &self.empty_entities;
         }
@@ -23,6 +23,17 @@ pub struct BaseEntityState {
     pub velocity: IVec2,
     #[serde(default)]
     pub player_creator_id: Option<u128>,
+    /// Movement smaller than a unit carried between steps, in units of
+    /// `1 / steps_per_second`. See `step_displacement`.
+    #[serde(default)]
+    pub position_remainder: IVec2,
+    /// Bit flags of the collision layers this entity is on.
+    #[serde(default)]
+    pub collision_layers: u32,
+    /// Bit flags of the collision layers this entity looks for contacts
+    /// with, see `Contacts`.
+    #[serde(default)]
+    pub collision_mask: u32,
 }
 
 impl Default for BaseEntityState {
@@ -33,10 +44,28 @@ impl Default for BaseEntityState {
             size: IVec2::default(),
             velocity: IVec2::default(),
             player_creator_id: None,
+            position_remainder: IVec2::default(),
+            collision_layers: 0,
+            collision_mask: 0,
         }
     }
 }
 
+impl BaseEntityState {
+    /// Whole units to move in a single step at `velocity` units per second.
+    /// The part of a unit left over is carried to the next step so slow
+    /// velocities still move and speeds aren't quantized. Integer only, so
+    /// results are identical on every platform and in zk.
+    pub fn step_displacement(&mut self, velocity: IVec2, steps_per_second: i32) -> IVec2 {
+        let steps_per_second = IVec2::splat(steps_per_second);
+        // an axis at rest carries nothing
+        let remainder = self.position_remainder * velocity.signum().abs();
+        let total = velocity + remainder;
+        self.position_remainder = total.rem_euclid(steps_per_second);
+        total.div_euclid(steps_per_second)
+    }
+}
+
 /// A _steppable_ entity that exists in the engine.
 pub trait SEEntity<G: GameLogic + 'static>: EEntity<G> {
     /// Return a boolean indicating whether the entity needs to mutate.
@@ -46,8 +75,20 @@ pub trait SEEntity<G: GameLogic + 'static>: EEntity<G> {
         true
     }
 
-    /// Mutate the next version of the entity.
+    /// Mutate the next version of the entity. Runs at the start of
+    /// `SystemPhase::PrePhysics`, before any attached system.
     fn step(&self, _engine: &GameEngine<G>, _next_self: &mut Self) {}
+
+    /// Called when a `SpawnEntity` event for this entity is applied. Runs
+    /// once all events of the step are applied, reading the engine as an
+    /// entity stepping in the next step would. Events registered here are
+    /// applied in the next step.
+    fn on_spawn(&self, _engine: &GameEngine<G>) {}
+
+    /// Called when a `RemoveEntity` event for this entity is applied, with
+    /// the entity as it was when removed. Runs at the same point as
+    /// `on_spawn`, after the entity is gone from the engine.
+    fn on_despawn(&self, _engine: &GameEngine<G>) {}
 }
 
 /// An entity that exists inside the engine.
@@ -58,6 +99,18 @@ pub trait EEntity<G: GameLogic + 'static>: Debug + Any + Clone {
     fn state(&self) -> &BaseEntityState;
     fn state_mut(&mut self) -> &mut BaseEntityState;
 
+    /// Systems that must be attached for the entity to be spawned, or for
+    /// a system to be attached to it. Checked by the engine when applying
+    /// `SpawnEntity` and `SpawnSystem` events.
+    fn required_systems(&self) -> Vec<SystemKind> {
+        vec![]
+    }
+
+    /// Systems that may never be attached to the entity.
+    fn forbidden_systems(&self) -> Vec<SystemKind> {
+        vec![]
+    }
+
     fn systems_by_type<T: EEntitySystem<G> + 'static>(&self) -> Vec<&T> {
         self.systems()
             .iter()
@@ -117,97 +170,4 @@ pub trait EEntity<G: GameLogic + 'static>: Debug + Any + Clone {
         let size = self.size();
         IRect::new(pos.x, pos.y, pos.x + size.x, pos.y + size.y)
     }
-
-    fn step_systems(&self, engine: &GameEngine<G>, next_self_maybe: &mut Option<G::Entity>);
-}
-
-/// Properties that all engine entities have. This macro is optional, you may
-/// implement SEEntity explicitly elsewhere.
-#[macro_export]
-macro_rules! entity_struct {
-    // Pattern for struct with additional fields and trait implementation
-    (
-        $game_logic:ident,
-        $vis:vis struct $name:ident {
-            $(
-                $(#[$field_attr:meta])*
-                $field_vis:vis $field_name:ident: $field_type:ty
-            ),*
-            $(,)?
-        }
-    ) => {
-
-        #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
-        $vis struct $name {
-            #[serde(default)]
-            pub state: $crate::prelude::BaseEntityState,
-            #[serde(default)]
-            pub systems: Vec<$crate::RefPointer<<$game_logic as $crate::prelude::GameLogic>::System>>,
-            $(
-                $(#[$field_attr])*
-                $field_vis $field_name: $field_type,
-            )*
-        }
-
-
-        impl $name {
-            pub fn new(state: $crate::prelude::BaseEntityState, systems: Vec<$crate::RefPointer<<$game_logic as $crate::prelude::GameLogic>::System>>) -> Self {
-                Self {
-                    state,
-                    systems,
-                    ..Default::default()
-                }
-            }
-        }
-
-        impl $crate::prelude::EEntity<$game_logic> for $name {
-            fn systems(&self) -> &Vec<$crate::RefPointer<<$game_logic as $crate::prelude::GameLogic>::System>> {
-                &self.systems
-            }
-
-            fn systems_mut(&mut self) -> &mut Vec<$crate::RefPointer<<$game_logic as $crate::prelude::GameLogic>::System>> {
-                &mut self.systems
-            }
-
-            fn state(&self) -> &$crate::prelude::BaseEntityState {
-                &self.state
-            }
-
-            fn state_mut(&mut self) -> &mut $crate::prelude::BaseEntityState {
-                &mut self.state
-            }
-
-            fn step_systems(&self, engine: &$crate::prelude::GameEngine<$game_logic>, next_self_maybe: &mut Option<EngineEntity>) {
-                type EngineEntity = <$game_logic as $crate::prelude::GameLogic>::Entity;
-                type EngineSystem = <$game_logic as $crate::prelude::GameLogic>::System;
-                let mut next_systems: Vec<$crate::RefPointer<EngineSystem>> = Vec::new();
-                let entity_ptr = engine
-                    .entity_by_id_untyped(&self.id(), None)
-                    .expect("entity being stepped but not in engine");
-                for system in self.systems() {
-                    if !system.prestep(engine, &entity_ptr) {
-                        next_systems.push(system.clone());
-                        continue;
-                    }
-                    // the system has requested a clone, we need to clone the parent entity
-                    // as well
-                    if next_self_maybe.is_none() {
-                        *next_self_maybe = Some(EngineEntity::from(self.clone()));
-                    }
-                    let next_self = next_self_maybe.as_mut().unwrap();
-                    // systems determine whether a clone is necessary
-                    if let Some(next_system) = system.step(engine, entity_ptr, &mut *next_self) {
-                        next_systems.push($crate::RefPointer::from(next_system));
-                    } else {
-                        // No new value was returned, the system is removed
-                    }
-                }
-                // if we did a clone, insert next_systems into clone
-                if let Some(next_self) = next_self_maybe.as_mut() {
-                    *next_self.systems_mut() = next_systems;
-                }
-            }
-
-        }
-    };
 }
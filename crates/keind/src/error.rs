@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Events passed to `GameEngine::integrate_events` could not be
+/// integrated. The engine is left unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrateError {
+    /// The events are older than the history kept by the engine, see
+    /// `GameEngine::trailing_state_len`. The events should be rejected, or
+    /// the engine reloaded from an authoritative copy.
+    TooLate {
+        /// The step of the oldest event.
+        step_index: u64,
+        /// The oldest step the engine can rewind to.
+        oldest_step_index: u64,
+    },
+}
+
+impl fmt::Display for IntegrateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrateError::TooLate {
+                step_index,
+                oldest_step_index,
+            } => write!(
+                f,
+                "events at step {step_index} are older than the oldest rewindable step {oldest_step_index}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IntegrateError {}
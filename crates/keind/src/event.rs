@@ -28,6 +28,27 @@ pub enum EngineEvent<G: GameLogic> {
         system_ptr: RefPointer<G::System>,
         is_non_determinism: bool,
     },
+    SpawnWorldSystem {
+        id: u128,
+        system_ptr: RefPointer<G::WorldSystem>,
+        is_non_determinism: bool,
+    },
+    RemoveWorldSystem {
+        id: u128,
+        is_non_determinism: bool,
+    },
+}
+
+/// What to do with an engine event received from a client, see
+/// `GameLogic::validate_remote_event`.
+#[derive(Clone)]
+pub enum RemoteEventVerdict<G: GameLogic> {
+    /// Integrate the event as received.
+    Accept,
+    /// Discard the event, with the reason why.
+    Reject(String),
+    /// Integrate this event in place of the one received.
+    Transform(EngineEvent<G>),
 }
 
 pub trait EventNonDeterminism {
@@ -52,6 +73,12 @@ impl<G: GameLogic> EventNonDeterminism for EngineEvent<G> {
             EngineEvent::RemoveSystem {
                 is_non_determinism, ..
             } => *is_non_determinism,
+            EngineEvent::SpawnWorldSystem {
+                is_non_determinism, ..
+            } => *is_non_determinism,
+            EngineEvent::RemoveWorldSystem {
+                is_non_determinism, ..
+            } => *is_non_determinism,
         }
     }
 }
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::ops::Bound::Excluded;
+use std::ops::Bound::Included;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// Steps between the full copies of the inputs kept by `InputHistory`.
+const CHECKPOINT_INTERVAL: u64 = 30;
+
+/// Entity inputs for the steps kept in the engine history. Inputs only
+/// change when an `EngineEvent::Input` is applied, so instead of a copy of
+/// every input each step, the history stores the inputs at the oldest step
+/// and the changes made after it. A full copy is kept every
+/// `CHECKPOINT_INTERVAL` steps so a lookup replays at most that many steps
+/// of changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct InputHistory<I: Clone> {
+    /// The oldest step in the history.
+    base_step_index: u64,
+    /// Inputs at the end of `base_step_index`.
+    base: PersistentMap<u128, I>,
+    /// The newest step in the history.
+    last_step_index: u64,
+    /// Inputs set in each step after `base_step_index`, in the order they
+    /// were applied. Steps without changes are not stored.
+    changes: BTreeMap<u64, Vec<(u128, I)>>,
+    /// Inputs at the end of every step after `base_step_index` that is a
+    /// multiple of `CHECKPOINT_INTERVAL`.
+    checkpoints: BTreeMap<u64, PersistentMap<u128, I>>,
+}
+
+impl<I: Clone> InputHistory<I> {
+    /// A history holding only `inputs` at `step_index`.
+    pub fn new(step_index: u64, inputs: PersistentMap<u128, I>) -> Self {
+        Self {
+            base_step_index: step_index,
+            base: inputs,
+            last_step_index: step_index,
+            changes: BTreeMap::new(),
+            checkpoints: BTreeMap::new(),
+        }
+    }
+
+    pub fn contains(&self, step_index: &u64) -> bool {
+        (self.base_step_index..=self.last_step_index).contains(step_index)
+    }
+
+    /// Record the inputs set in `step_index`, which must follow the newest
+    /// step in the history.
+    pub fn record(&mut self, step_index: u64, changes: Vec<(u128, I)>) {
+        debug_assert!(step_index > self.last_step_index);
+        if !changes.is_empty() {
+            self.changes.insert(step_index, changes);
+        }
+        self.last_step_index = step_index;
+        if step_index % CHECKPOINT_INTERVAL == 0 {
+            let inputs = self.fold_to(&step_index);
+            self.checkpoints.insert(step_index, inputs);
+        }
+    }
+
+    /// The input of entity `id` at the end of `step_index`. `None` if the
+    /// step is outside the history, `Some(None)` if the entity has no input.
+    pub fn input_at(&self, step_index: &u64, id: &u128) -> Option<Option<&I>> {
+        if !self.contains(step_index) {
+            return None;
+        }
+        let (checkpoint_step_index, checkpoint) = self.checkpoint_at(step_index);
+        let changed = self
+            .changes
+            .range((Excluded(checkpoint_step_index), Included(*step_index)))
+            .rev()
+            .flat_map(|(_, changes)| changes.iter().rev())
+            .find(|(entity_id, _)| entity_id == id)
+            .map(|(_, input)| input);
+        Some(changed.or_else(|| checkpoint.get(id)))
+    }
+
+    /// Every input at the end of `step_index`, if the step is in the history.
+    pub fn inputs_at(&self, step_index: &u64) -> Option<PersistentMap<u128, I>> {
+        if !self.contains(step_index) {
+            return None;
+        }
+        Some(self.fold_to(step_index))
+    }
+
+    /// The newest full copy of the inputs at or before `step_index`.
+    fn checkpoint_at(&self, step_index: &u64) -> (u64, &PersistentMap<u128, I>) {
+        self.checkpoints
+            .range(..=step_index)
+            .next_back()
+            .map(|(checkpoint_step_index, inputs)| (*checkpoint_step_index, inputs))
+            .unwrap_or((self.base_step_index, &self.base))
+    }
+
+    /// Apply the changes until `step_index` to the checkpoint before it.
+    fn fold_to(&self, step_index: &u64) -> PersistentMap<u128, I> {
+        let (checkpoint_step_index, checkpoint) = self.checkpoint_at(step_index);
+        let mut inputs = checkpoint.clone();
+        for (_, changes) in self
+            .changes
+            .range((Excluded(checkpoint_step_index), Included(*step_index)))
+        {
+            for (id, input) in changes {
+                inputs.insert(*id, input.clone());
+            }
+        }
+        inputs
+    }
+
+    /// Drop the steps before `step_index`, folding their changes into the
+    /// oldest kept step.
+    pub fn forget_before(&mut self, step_index: u64) {
+        if step_index <= self.base_step_index {
+            return;
+        }
+        self.base = self.fold_to(&step_index);
+        self.changes = self.changes.split_off(&(step_index + 1));
+        self.checkpoints = self.checkpoints.split_off(&(step_index + 1));
+        self.base_step_index = step_index;
+        self.last_step_index = self.last_step_index.max(step_index);
+    }
+
+    /// Drop the steps after `step_index`.
+    pub fn forget_after(&mut self, step_index: u64) {
+        self.changes.split_off(&(step_index + 1));
+        self.checkpoints.split_off(&(step_index + 1));
+        self.last_step_index = self.last_step_index.min(step_index);
+    }
+}
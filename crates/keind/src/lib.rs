@@ -2,14 +2,28 @@
 /// in zkvm environments. As a result it's quick in most other
 /// environments.
 ///
+mod constraint;
+mod contact;
+mod diagnostic;
+mod diff;
 mod engine;
 mod entity;
+mod error;
 mod event;
+mod input;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod persistent_map;
 pub mod prelude;
+mod replay;
+mod snapshot;
+mod spatial;
 mod system;
 #[cfg(test)]
 mod test;
 
+pub use keind_macros::entity;
+
 /// In a zkvm we are truly single threaded, and have no use for atomics.
 /// We try to remove all atomics and thread support to improve performance.
 #[cfg(feature = "zk")]
@@ -17,6 +31,14 @@ pub use std::rc::Rc as RefPointer;
 #[cfg(not(feature = "zk"))]
 pub use std::sync::Arc as RefPointer;
 
+#[cfg(all(feature = "zk", feature = "parallel"))]
+compile_error!("the parallel feature requires threads and can't be used with zk");
+
+#[cfg(not(feature = "zk"))]
+use once_cell::sync::OnceCell;
+#[cfg(feature = "zk")]
+use once_cell::unsync::OnceCell;
+
 use serde::Deserialize;
 use serde::Serialize;
 use std::fmt::Debug;
@@ -38,7 +60,17 @@ pub trait GameLogic: Clone + Serialize + for<'de> Deserialize<'de> + 'static {
         + Clone
         + Serialize
         + for<'de> Deserialize<'de>; // Enum wrapping all possible entities
-    type System: KPoly + Debug + Clone + Serialize + for<'de> Deserialize<'de>; // Enum wrapping all possible systems
+    type System: system::EEntitySystem<Self>
+        + KPoly
+        + Debug
+        + Clone
+        + Serialize
+        + for<'de> Deserialize<'de>; // Enum wrapping all possible systems
+    type WorldSystem: system::EWorldSystem<Self>
+        + Debug
+        + Clone
+        + Serialize
+        + for<'de> Deserialize<'de>; // Enum wrapping all possible world systems
     type Input: Default + Clone + Serialize + for<'de> Deserialize<'de>; // User ninput
     type Event: Clone + Serialize + for<'de> Deserialize<'de>; // Game event, distinct from Engine event, which is internal to keind
 
@@ -46,6 +78,18 @@ pub trait GameLogic: Clone + Serialize + for<'de> Deserialize<'de> + 'static {
         engine: &mut engine::GameEngine<Self>,
         game_events: &Vec<RefPointer<Self::Event>>,
     );
+
+    /// Decide what to do with an engine event a client sent for
+    /// `step_index`. `sender` is the id of the entity the client controls.
+    /// Remote events are rejected unless the game logic accepts them.
+    fn validate_remote_event(
+        _engine: &engine::GameEngine<Self>,
+        _sender: &u128,
+        _step_index: &u64,
+        _event: &event::EngineEvent<Self>,
+    ) -> event::RemoteEventVerdict<Self> {
+        event::RemoteEventVerdict::Reject("remote events are not accepted".to_string())
+    }
 }
 
 #[cfg(not(feature = "zk"))]
@@ -58,12 +102,38 @@ pub trait GameLogic: Clone + Serialize + for<'de> Deserialize<'de> + 'static {
         + Clone
         + Serialize
         + for<'de> Deserialize<'de>; // Enum wrapping all possible entities
-    type System: KPoly + Debug + Clone + Serialize + for<'de> Deserialize<'de>; // Enum wrapping all possible systems
-    type Input: Default + Clone + Serialize + for<'de> Deserialize<'de>; // User ninput
-    type Event: Clone + Serialize + for<'de> Deserialize<'de>; // Game event, distinct from Engine event, which is internal to keind
+    type System: system::EEntitySystem<Self>
+        + Send
+        + Sync
+        + KPoly
+        + Debug
+        + Clone
+        + Serialize
+        + for<'de> Deserialize<'de>; // Enum wrapping all possible systems
+    type WorldSystem: system::EWorldSystem<Self>
+        + Send
+        + Sync
+        + Debug
+        + Clone
+        + Serialize
+        + for<'de> Deserialize<'de>; // Enum wrapping all possible world systems
+    type Input: Default + Send + Sync + Clone + Serialize + for<'de> Deserialize<'de>; // User ninput
+    type Event: Send + Sync + Clone + Serialize + for<'de> Deserialize<'de>; // Game event, distinct from Engine event, which is internal to keind
 
     fn handle_game_events(
         engine: &mut engine::GameEngine<Self>,
         game_events: &Vec<RefPointer<Self::Event>>,
     );
+
+    /// Decide what to do with an engine event a client sent for
+    /// `step_index`. `sender` is the id of the entity the client controls.
+    /// Remote events are rejected unless the game logic accepts them.
+    fn validate_remote_event(
+        _engine: &engine::GameEngine<Self>,
+        _sender: &u128,
+        _step_index: &u64,
+        _event: &event::EngineEvent<Self>,
+    ) -> event::RemoteEventVerdict<Self> {
+        event::RemoteEventVerdict::Reject("remote events are not accepted".to_string())
+    }
 }
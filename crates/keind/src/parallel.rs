@@ -0,0 +1,79 @@
+/// Support for stepping entities on multiple threads.
+///
+/// Entities register events through `&GameEngine<G>`, so when they step
+/// concurrently the order events arrive in the engine channels depends
+/// on scheduling. While an entity steps on a rayon thread its events are
+/// captured in a thread local instead, and the engine sends them in the
+/// order a serial step would have.
+use std::any::Any;
+use std::cell::RefCell;
+
+use crate::prelude::*;
+
+thread_local! {
+    static CAPTURED_EVENTS: RefCell<Option<Box<dyn Any>>> = const { RefCell::new(None) };
+}
+
+/// Events registered while running a `capture_events` closure.
+pub(crate) struct CapturedEvents<G: GameLogic> {
+    pub engine_events: Vec<(u64, EngineEvent<G>)>,
+    pub game_events: Vec<G::Event>,
+}
+
+impl<G: GameLogic> Default for CapturedEvents<G> {
+    fn default() -> Self {
+        Self {
+            engine_events: Vec::new(),
+            game_events: Vec::new(),
+        }
+    }
+}
+
+/// Run `f`, capturing the events it registers on this thread instead of
+/// sending them.
+pub(crate) fn capture_events<G: GameLogic>(f: impl FnOnce()) -> CapturedEvents<G> {
+    CAPTURED_EVENTS.with_borrow_mut(|captured| {
+        assert!(captured.is_none(), "nested event capture");
+        *captured = Some(Box::new(CapturedEvents::<G>::default()));
+    });
+    f();
+    CAPTURED_EVENTS
+        .with_borrow_mut(|captured| captured.take())
+        .and_then(|captured| captured.downcast::<CapturedEvents<G>>().ok())
+        .map(|captured| *captured)
+        .expect("captured events missing")
+}
+
+/// Capture `event` if called inside `capture_events`, otherwise return it.
+pub(crate) fn capture_engine_event<G: GameLogic>(
+    event: (u64, EngineEvent<G>),
+) -> Option<(u64, EngineEvent<G>)> {
+    CAPTURED_EVENTS.with_borrow_mut(|captured| {
+        match captured
+            .as_mut()
+            .and_then(|captured| captured.downcast_mut::<CapturedEvents<G>>())
+        {
+            Some(captured) => {
+                captured.engine_events.push(event);
+                None
+            }
+            None => Some(event),
+        }
+    })
+}
+
+/// Capture `event` if called inside `capture_events`, otherwise return it.
+pub(crate) fn capture_game_event<G: GameLogic>(event: G::Event) -> Option<G::Event> {
+    CAPTURED_EVENTS.with_borrow_mut(|captured| {
+        match captured
+            .as_mut()
+            .and_then(|captured| captured.downcast_mut::<CapturedEvents<G>>())
+        {
+            Some(captured) => {
+                captured.game_events.push(event);
+                None
+            }
+            None => Some(event),
+        }
+    })
+}
@@ -0,0 +1,115 @@
+use std::fmt::Debug;
+use std::ops::Deref;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+
+/// Same pointer split as `RefPointer`, no atomics in zk.
+#[cfg(feature = "zk")]
+type Inner<K, V> = rpds::RedBlackTreeMap<K, V>;
+#[cfg(not(feature = "zk"))]
+type Inner<K, V> = rpds::RedBlackTreeMapSync<K, V>;
+
+/// An ordered map with structural sharing. Clones are O(1) and share every
+/// unchanged entry, so keeping a copy for each step costs memory proportional
+/// to what changed in the step rather than the size of the map.
+///
+/// Read access is through `Deref` to the underlying `rpds` map.
+pub struct PersistentMap<K: Ord, V>(Inner<K, V>);
+
+impl<K: Ord, V> PersistentMap<K, V> {
+    pub fn new() -> Self {
+        Self(Inner::new_with_ptr_kind())
+    }
+
+    /// Insert a value, returning the previous value for `key`, if any.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V>
+    where
+        V: Clone,
+    {
+        let out = self.0.get(&key).cloned();
+        self.0.insert_mut(key, value);
+        out
+    }
+
+    /// Remove a value, returning it if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V>
+    where
+        V: Clone,
+    {
+        let out = self.0.get(key).cloned();
+        if out.is_some() {
+            self.0.remove_mut(key);
+        }
+        out
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<K: Ord, V> Deref for PersistentMap<K, V> {
+    type Target = Inner<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K: Ord, V> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K: Ord + Debug, V: Debug> Debug for PersistentMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+impl<K: Ord, V: PartialEq> PartialEq for PersistentMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for PersistentMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self(Inner::from_iter(iter))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a PersistentMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = <&'a Inner<K, V> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        (&self.0).into_iter()
+    }
+}
+
+impl<K: Ord + Serialize, V: Serialize> Serialize for PersistentMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, K: Ord + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for PersistentMap<K, V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self(Inner::deserialize(deserializer)?))
+    }
+}
@@ -6,15 +6,51 @@ pub use crate::GameLogic;
 pub use crate::KPoly;
 pub use crate::RefPointer;
 
+pub use crate::constraint::SystemKind;
+pub use crate::constraint::SystemViolation;
+
+pub use crate::contact::Contact;
+pub use crate::contact::ContactPhase;
+pub use crate::contact::Contacts;
+
+pub use crate::diagnostic::EngineDiagnostic;
+pub use crate::diagnostic::MAX_PENDING_DIAGNOSTICS;
+
+pub use crate::diff::DesyncReport;
+pub use crate::diff::EntityDiff;
+pub use crate::diff::EntityDiffKind;
+pub use crate::diff::FieldDiff;
+pub use crate::diff::diff_entity_maps;
+pub use crate::diff::diff_fields;
+pub use crate::diff::flatten_fields;
+
 pub use crate::engine::GameEngine;
 
 pub use crate::entity::BaseEntityState;
 pub use crate::entity::EEntity;
 pub use crate::entity::SEEntity;
 
+pub use crate::error::IntegrateError;
+
 pub use crate::event::EngineEvent;
 pub use crate::event::EventNonDeterminism;
+pub use crate::event::RemoteEventVerdict;
 
-pub use crate::system::EEntitySystem;
+pub use crate::persistent_map::PersistentMap;
+
+pub use crate::replay::DEFAULT_REPLAY_HASH_INTERVAL;
+pub use crate::replay::Replay;
+pub use crate::replay::ReplayDivergence;
+pub use crate::replay::ReplayRecord;
+pub use crate::replay::ReplayReport;
+pub use crate::replay::ReplayWriter;
+
+pub use crate::snapshot::EngineSnapshot;
+pub use crate::snapshot::SNAPSHOT_FORMAT_VERSION;
+pub use crate::snapshot::SnapshotHeader;
 
-pub use crate::entity_struct;
+pub use crate::spatial::SpatialIndex;
+
+pub use crate::system::EEntitySystem;
+pub use crate::system::EWorldSystem;
+pub use crate::system::SystemPhase;
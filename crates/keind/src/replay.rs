@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::io::Write;
+use std::marker::PhantomData;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// Steps between recorded step hashes unless otherwise configured.
+pub const DEFAULT_REPLAY_HASH_INTERVAL: u64 = 60;
+
+/// A single entry in a replay, recorded in the order it happened.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "G: for<'dee> serde::Deserialize<'dee>"))]
+pub enum ReplayRecord<G: GameLogic> {
+    /// Events passed to `GameEngine::integrate_events` while the engine
+    /// was at step `integrated_at`.
+    Events {
+        integrated_at: u64,
+        events: BTreeMap<u64, Vec<EngineEvent<G>>>,
+    },
+    /// The hash of `step_index` observed while the engine was at
+    /// `checked_at`. Events integrated later may rewrite the step, so the
+    /// hash is only comparable at the same point in the replay.
+    StepHash {
+        checked_at: u64,
+        step_index: u64,
+        hash: blake3::Hash,
+    },
+}
+
+/// Streams a replay to `W`. A replay is an `EngineSnapshot` followed by
+/// `ReplayRecord`s, each flushed as it's recorded so a crash leaves a
+/// readable file.
+pub struct ReplayWriter<G: GameLogic, W: Write> {
+    writer: W,
+    pub hash_interval: u64,
+    last_hash_step: u64,
+    _phantom: PhantomData<G>,
+}
+
+impl<G: GameLogic, W: Write> ReplayWriter<G, W> {
+    /// Start a replay from the current state of `engine`.
+    pub fn new(
+        mut writer: W,
+        engine: &GameEngine<G>,
+        game_data_hash: blake3::Hash,
+    ) -> Result<Self> {
+        let snapshot = engine.snapshot(game_data_hash).to_bytes()?;
+        bincode::serialize_into(&mut writer, &snapshot)?;
+        writer.flush()?;
+        Ok(Self {
+            writer,
+            hash_interval: DEFAULT_REPLAY_HASH_INTERVAL,
+            last_hash_step: *engine.step_index(),
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn record_events(
+        &mut self,
+        integrated_at: u64,
+        events: &BTreeMap<u64, Vec<EngineEvent<G>>>,
+    ) -> Result<()> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.write_record(&ReplayRecord::Events {
+            integrated_at,
+            events: events.clone(),
+        })
+    }
+
+    /// Record the hash of the current step if `hash_interval` steps have
+    /// passed since the last recorded hash.
+    pub fn record_step_hash(&mut self, engine: &GameEngine<G>) -> Result<()> {
+        let step_index = *engine.step_index();
+        if step_index < self.last_hash_step + self.hash_interval {
+            return Ok(());
+        }
+        self.last_hash_step = step_index;
+        self.write_record(&ReplayRecord::StepHash {
+            checked_at: step_index,
+            step_index,
+            hash: engine.step_hash(&step_index)?,
+        })
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_record(&mut self, record: &ReplayRecord<G>) -> Result<()> {
+        bincode::serialize_into(&mut self.writer, record)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// The first recorded hash that did not match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayDivergence {
+    pub step_index: u64,
+    /// The last step with a matching hash, the divergence happened after this.
+    pub last_matching_step: Option<u64>,
+    pub expected: blake3::Hash,
+    pub actual: blake3::Hash,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    pub start_step: u64,
+    pub end_step: u64,
+    pub checked_hashes: usize,
+    pub divergence: Option<ReplayDivergence>,
+}
+
+/// A replay read back from a `ReplayWriter`.
+pub struct Replay<G: GameLogic> {
+    pub snapshot: EngineSnapshot<G>,
+    pub records: Vec<ReplayRecord<G>>,
+}
+
+impl<G: GameLogic> Replay<G> {
+    pub fn read<R: BufRead>(mut reader: R) -> Result<Self> {
+        let snapshot_bytes: Vec<u8> = bincode::deserialize_from(&mut reader)?;
+        let snapshot = EngineSnapshot::from_bytes(&snapshot_bytes)?;
+        let mut records = Vec::new();
+        while !reader.fill_buf()?.is_empty() {
+            records.push(bincode::deserialize_from(&mut reader)?);
+        }
+        Ok(Self { snapshot, records })
+    }
+
+    /// Re-run the replay, stopping at the first recorded hash that does
+    /// not match.
+    pub fn verify(self, game_data_hash: &blake3::Hash) -> Result<ReplayReport> {
+        Ok(self.run(game_data_hash)?.1)
+    }
+
+    /// Like `verify`, also returning the engine at the point the replay
+    /// stopped, e.g. to compare with `GameEngine::find_desync`.
+    pub fn run(self, game_data_hash: &blake3::Hash) -> Result<(GameEngine<G>, ReplayReport)> {
+        let mut engine = GameEngine::<G>::from_snapshot(self.snapshot, game_data_hash)?;
+        let mut report = ReplayReport {
+            start_step: *engine.step_index(),
+            end_step: *engine.step_index(),
+            checked_hashes: 0,
+            divergence: None,
+        };
+        let mut last_matching_step = None;
+        for record in self.records {
+            match record {
+                ReplayRecord::Events {
+                    integrated_at,
+                    events,
+                } => {
+                    if &integrated_at > engine.step_index() {
+                        engine.step_to(&integrated_at);
+                    }
+                    engine.integrate_events(events)?;
+                }
+                ReplayRecord::StepHash {
+                    checked_at,
+                    step_index,
+                    hash,
+                } => {
+                    if &checked_at > engine.step_index() {
+                        engine.step_to(&checked_at);
+                    }
+                    let actual = engine.step_hash(&step_index)?;
+                    report.checked_hashes += 1;
+                    if actual != hash {
+                        report.end_step = *engine.step_index();
+                        report.divergence = Some(ReplayDivergence {
+                            step_index,
+                            last_matching_step,
+                            expected: hash,
+                            actual,
+                        });
+                        return Ok((engine, report));
+                    }
+                    last_matching_step = Some(step_index);
+                }
+            }
+        }
+        report.end_step = *engine.step_index();
+        Ok((engine, report))
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// Incremented whenever the snapshot layout changes. Snapshots with a
+/// different version are rejected rather than partially decoded.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Encoded ahead of the engine state so a snapshot can be identified
+/// without decoding the whole thing.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    pub format_version: u32,
+    pub engine_id: u128,
+    pub step_index: u64,
+    /// Hash of the static game data the engine was running with. Restoring
+    /// against different data would not be deterministic.
+    pub game_data_hash: blake3::Hash,
+}
+
+/// Everything needed to continue an engine deterministically. This includes
+/// the state the engine skips when serialized directly: game events and
+/// events waiting in the engine channels.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "G: for<'dee> serde::Deserialize<'dee>"))]
+pub struct EngineSnapshot<G: GameLogic> {
+    pub header: SnapshotHeader,
+    pub(crate) engine: GameEngine<G>,
+    pub(crate) game_events_by_step: BTreeMap<u64, Vec<RefPointer<G::Event>>>,
+    /// Engine events registered but not yet received by a step, including
+    /// those queued for future steps.
+    pub(crate) pending_engine_events: Vec<(u64, EngineEvent<G>)>,
+    pub(crate) pending_game_events: Vec<G::Event>,
+}
+
+impl<G: GameLogic> EngineSnapshot<G> {
+    /// The engine state, without restoring pending events.
+    pub fn engine(&self) -> &GameEngine<G> {
+        &self.engine
+    }
+
+    /// Decode only the header of an encoded snapshot.
+    pub fn read_header(bytes: &[u8]) -> Result<SnapshotHeader> {
+        let mut reader = bytes;
+        let header: SnapshotHeader = bincode::deserialize_from(&mut reader)?;
+        if header.format_version != SNAPSHOT_FORMAT_VERSION {
+            anyhow::bail!(
+                "unsupported snapshot format version {}, expected {SNAPSHOT_FORMAT_VERSION}",
+                header.format_version
+            );
+        }
+        Ok(header)
+    }
+
+    /// Encode as the header followed by the engine state.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        // check the version before decoding anything else
+        Self::read_header(bytes)?;
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+use bevy_math::IRect;
+use bevy_math::IVec2;
+
+use crate::prelude::*;
+
+/// Width and height of a single grid cell, in engine units.
+pub const DEFAULT_CELL_SIZE: i32 = 128;
+
+/// A uniform grid over the entities present in the current step.
+///
+/// The index is derived data. It's built from `GameEngine::entities` and
+/// never serialized, so a rewound or deserialized engine rebuilds an
+/// identical index. Queries always return ids in ascending order, matching
+/// the iteration order of the engine `BTreeMap`.
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    cell_size: i32,
+    cells: HashMap<IVec2, Vec<u128>>,
+    rects: HashMap<u128, IRect>,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: i32) -> Self {
+        assert!(cell_size > 0, "spatial index cell size must be positive");
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            rects: HashMap::default(),
+        }
+    }
+
+    /// Build an index from an id keyed map of entities.
+    pub fn from_entities<'a, G: GameLogic>(
+        entities: impl IntoIterator<Item = (&'a u128, &'a RefPointer<G::Entity>)>,
+    ) -> Self {
+        let mut out = Self::default();
+        for (id, entity) in entities {
+            out.insert(*id, Self::entity_rect::<G>(entity));
+        }
+        out
+    }
+
+    /// Entities may be placed far out of bounds (e.g. `IVec2::MAX` while
+    /// waiting to be attached), so compute the rect without overflowing.
+    pub fn entity_rect<G: GameLogic>(entity: &G::Entity) -> IRect {
+        let pos = entity.position();
+        let size = entity.size();
+        IRect {
+            min: pos,
+            max: pos.saturating_add(size),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn rect(&self, id: &u128) -> Option<&IRect> {
+        self.rects.get(id)
+    }
+
+    /// Insert or move an entity.
+    pub fn insert(&mut self, id: u128, rect: IRect) {
+        if let Some(old_rect) = self.rects.get(&id) {
+            if old_rect == &rect {
+                return;
+            }
+            self.remove(&id);
+        }
+        for cell in self.cells_for(&rect) {
+            self.cells.entry(cell).or_default().push(id);
+        }
+        self.rects.insert(id, rect);
+    }
+
+    pub fn remove(&mut self, id: &u128) {
+        if let Some(rect) = self.rects.remove(id) {
+            for cell in self.cells_for(&rect) {
+                if let Some(ids) = self.cells.get_mut(&cell) {
+                    ids.retain(|v| v != id);
+                    if ids.is_empty() {
+                        self.cells.remove(&cell);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ids of all entities with a non-empty intersection with `rect`,
+    /// in ascending order.
+    pub fn query(&self, rect: &IRect) -> Vec<u128> {
+        if rect.is_empty() {
+            return vec![];
+        }
+        let mut out = BTreeSet::new();
+        for cell in self.cells_for(rect) {
+            if let Some(ids) = self.cells.get(&cell) {
+                for id in ids {
+                    if out.contains(id) {
+                        continue;
+                    }
+                    // unwrap: every id in a cell has a rect
+                    if !self.rects.get(id).unwrap().intersect(*rect).is_empty() {
+                        out.insert(*id);
+                    }
+                }
+            }
+        }
+        out.into_iter().collect()
+    }
+
+    fn cells_for(&self, rect: &IRect) -> impl Iterator<Item = IVec2> + use<> {
+        let min = rect.min.div_euclid(IVec2::splat(self.cell_size));
+        // max is exclusive
+        let max = rect
+            .max
+            .saturating_sub(IVec2::ONE)
+            .max(rect.min)
+            .div_euclid(IVec2::splat(self.cell_size));
+        (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+    }
+}
@@ -1,7 +1,39 @@
 use std::any::Any;
 
+use serde::Deserialize;
+use serde::Serialize;
+
 use crate::prelude::*;
 
+/// The phases of the modification part of a step. Every entity completes
+/// a phase before any entity starts the next, and within a phase entities
+/// run in id order.
+///
+/// The entity's own `SEEntity::step` runs at the start of `PrePhysics`,
+/// before any of its systems.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SystemPhase {
+    /// Forces and input, e.g. gravity.
+    PrePhysics,
+    /// Movement and collision.
+    Physics,
+    /// Anything that depends on the result of movement, e.g. following
+    /// another entity.
+    PostPhysics,
+    /// Timers, despawning, and other bookkeeping.
+    Cleanup,
+}
+
+impl SystemPhase {
+    /// All phases in execution order.
+    pub const ALL: [SystemPhase; 4] = [
+        SystemPhase::PrePhysics,
+        SystemPhase::Physics,
+        SystemPhase::PostPhysics,
+        SystemPhase::Cleanup,
+    ];
+}
+
 /// A system that is attached by pointer to an entity in the engine.
 /// Systems determine when they "step", which involves copying their
 /// state and returning a new mutated instance.
@@ -9,6 +41,40 @@ use crate::prelude::*;
 /// This allows entities to be constant size in memory (vector of pointers)
 /// with granular copy on change behavior.
 pub trait EEntitySystem<G: GameLogic>: Any {
+    /// The phase of the step this system runs in.
+    fn phase(&self) -> SystemPhase {
+        SystemPhase::Physics
+    }
+
+    /// Order within a phase, lower runs first.
+    fn priority(&self) -> i32 {
+        0
+    }
+
+    /// The type of this system. Wrapper enums return the type they wrap.
+    fn kind(&self) -> SystemKind
+    where
+        Self: Sized,
+    {
+        SystemKind::of::<Self>()
+    }
+
+    /// Whether at most one system of this type may be attached to an
+    /// entity.
+    fn unique(&self) -> bool {
+        false
+    }
+
+    /// Systems the entity must also have attached.
+    fn required_systems(&self) -> Vec<SystemKind> {
+        vec![]
+    }
+
+    /// Systems the entity may not have attached alongside this one.
+    fn forbidden_systems(&self) -> Vec<SystemKind> {
+        vec![]
+    }
+
     /// Readonly access to entity. Determine if write access
     /// is needed.
     /// Return true to mutate self or entity
@@ -21,9 +87,9 @@ pub trait EEntitySystem<G: GameLogic>: Any {
     /// is attached to. Underlying entity type may be extracted with, for example,
     /// `let player_entity = entity.extract_ref_mut::<PlayerEntity>()`
     ///
-    /// the system may freely mutate the entity. The entity step logic executes _after_
-    /// all system steps. Oldest systems execute first (e.g.) system added at step 1 executes
-    /// before system added at step 5.
+    /// the system may freely mutate the entity. Systems execute by `phase`, then by
+    /// `priority`. Systems with the same phase and priority execute oldest first (e.g.)
+    /// system added at step 1 executes before system added at step 5.
     fn step(
         &self,
         _engine: &GameEngine<G>,
@@ -36,3 +102,33 @@ pub trait EEntitySystem<G: GameLogic>: Any {
         None
     }
 }
+
+/// A system registered on the engine rather than an entity. World systems
+/// step once per step, in id order, after all entity systems. Like entity
+/// systems they read the state of the previous step and may register
+/// `EngineEvent`s, e.g. to spawn entities.
+///
+/// World systems are part of the engine history and `GameEngine::step_hash`.
+pub trait EWorldSystem<G: GameLogic>: Any {
+    /// Readonly access to the engine. Return true to step the system.
+    fn prestep(&self, _engine: &GameEngine<G>, _id: &u128) -> bool {
+        true
+    }
+
+    /// Step the system, returning the next version of it. Returning `None`
+    /// removes the system. `id` is stable for the lifetime of the system
+    /// and may be used to seed an rng.
+    fn step(&self, _engine: &GameEngine<G>, _id: &u128) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// For games without world systems.
+impl<G: GameLogic> EWorldSystem<G> for () {
+    fn prestep(&self, _engine: &GameEngine<G>, _id: &u128) -> bool {
+        false
+    }
+}
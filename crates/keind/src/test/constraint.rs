@@ -0,0 +1,169 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::*;
+
+#[crate::entity(TestGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct ConstrainedEntity {}
+
+impl SEEntity<TestGameLogic> for ConstrainedEntity {}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkerSystem;
+
+impl EEntitySystem<TestGameLogic> for MarkerSystem {
+    fn prestep(
+        &self,
+        _engine: &GameEngine<TestGameLogic>,
+        _entity: &<TestGameLogic as GameLogic>::Entity,
+    ) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NeedsMarkerSystem;
+
+impl EEntitySystem<TestGameLogic> for NeedsMarkerSystem {
+    fn prestep(
+        &self,
+        _engine: &GameEngine<TestGameLogic>,
+        _entity: &<TestGameLogic as GameLogic>::Entity,
+    ) -> bool {
+        false
+    }
+}
+
+fn constrained(id: u128, systems: Vec<EngineEntitySystem>) -> EngineEntity {
+    ConstrainedEntity::new(
+        BaseEntityState {
+            id,
+            ..Default::default()
+        },
+        systems.into_iter().map(RefPointer::new).collect(),
+    )
+    .into()
+}
+
+fn missing(system: &str) -> SystemViolation {
+    SystemViolation::Missing {
+        system: system.to_string(),
+    }
+}
+
+#[test]
+fn should_reject_entities_breaking_constraints() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.spawn_entity(constrained(1, vec![]));
+    engine.spawn_entity(constrained(2, vec![MarkerSystem.into(), TestSystem.into()]));
+    engine.spawn_entity(constrained(
+        3,
+        vec![MarkerSystem.into(), MarkerSystem.into()],
+    ));
+    engine.spawn_entity(constrained(4, vec![MarkerSystem.into()]));
+    engine.step();
+
+    assert_eq!(engine.entity_count(), 1);
+    assert!(engine.entity_by_id::<ConstrainedEntity>(&4, None).is_some());
+    assert_eq!(
+        engine.drain_diagnostics(),
+        vec![
+            (
+                0,
+                EngineDiagnostic::RejectedEntity {
+                    entity_id: 1,
+                    violation: missing("MarkerSystem"),
+                }
+            ),
+            (
+                0,
+                EngineDiagnostic::RejectedEntity {
+                    entity_id: 2,
+                    violation: SystemViolation::Forbidden {
+                        system: "TestSystem".to_string(),
+                    },
+                }
+            ),
+            (
+                0,
+                EngineDiagnostic::RejectedEntity {
+                    entity_id: 3,
+                    violation: SystemViolation::Duplicate {
+                        system: "MarkerSystem".to_string(),
+                    },
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn should_reject_systems_breaking_constraints() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.spawn_entity(constrained(1, vec![MarkerSystem.into()]));
+    engine.spawn_entity(
+        TestEntity::new(
+            BaseEntityState {
+                id: 2,
+                ..Default::default()
+            },
+            vec![],
+        )
+        .into(),
+    );
+    engine.step();
+
+    engine.spawn_system(1, MarkerSystem.into());
+    engine.spawn_system(1, TestSystem.into());
+    engine.spawn_system(2, NeedsMarkerSystem.into());
+    // satisfied by the system already attached
+    engine.spawn_system(1, NeedsMarkerSystem.into());
+    engine.step();
+
+    let systems = engine
+        .entity_by_id_untyped(&1, None)
+        .unwrap()
+        .systems()
+        .iter()
+        .map(|system| system.kind().name())
+        .collect::<Vec<_>>();
+    assert_eq!(systems, vec!["MarkerSystem", "NeedsMarkerSystem"]);
+    assert!(
+        engine
+            .entity_by_id_untyped(&2, None)
+            .unwrap()
+            .systems()
+            .is_empty()
+    );
+    assert_eq!(
+        engine.drain_diagnostics(),
+        vec![
+            (
+                1,
+                EngineDiagnostic::RejectedSystem {
+                    entity_id: 1,
+                    violation: SystemViolation::Duplicate {
+                        system: "MarkerSystem".to_string(),
+                    },
+                }
+            ),
+            (
+                1,
+                EngineDiagnostic::RejectedSystem {
+                    entity_id: 1,
+                    violation: SystemViolation::Forbidden {
+                        system: "TestSystem".to_string(),
+                    },
+                }
+            ),
+            (
+                1,
+                EngineDiagnostic::RejectedSystem {
+                    entity_id: 2,
+                    violation: missing("MarkerSystem"),
+                }
+            ),
+        ]
+    );
+}
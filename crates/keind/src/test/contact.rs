@@ -0,0 +1,96 @@
+use anyhow::Result;
+
+use super::*;
+
+const A: u32 = 1 << 0;
+const B: u32 = 1 << 1;
+
+fn spawn(engine: &GameEngine<TestGameLogic>, id: u128, x: i32, layers: u32, mask: u32) {
+    engine.register_event(
+        None,
+        EngineEvent::SpawnEntity {
+            entity: RefPointer::new(
+                TestEntity::new(
+                    BaseEntityState {
+                        id,
+                        position: IVec2::new(x, 0),
+                        size: IVec2::new(10, 10),
+                        collision_layers: layers,
+                        collision_mask: mask,
+                        ..Default::default()
+                    },
+                    vec![],
+                )
+                .into(),
+            ),
+            is_non_determinism: true,
+        },
+    );
+}
+
+fn contact(other: u128, phase: ContactPhase) -> Contact {
+    Contact { other, phase }
+}
+
+#[test]
+fn should_filter_contacts_by_layer() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    spawn(&engine, 1, 0, A, B);
+    spawn(&engine, 2, 5, B, 0);
+    // on a layer 1 doesn't look for
+    spawn(&engine, 3, 5, A, 0);
+    // looks for 1 and 3 itself
+    spawn(&engine, 4, 5, 0, A);
+    // on the right layer, not overlapping
+    spawn(&engine, 5, 50, B, 0);
+    engine.step();
+
+    let contacts = engine.contacts();
+    assert_eq!(
+        contacts.touching(&1).copied().collect::<Vec<_>>(),
+        vec![2, 4]
+    );
+    assert_eq!(contacts.touching(&2).copied().collect::<Vec<_>>(), vec![1]);
+    // only found by 4
+    assert_eq!(contacts.touching(&3).copied().collect::<Vec<_>>(), vec![4]);
+    assert!(!contacts.is_touching(&1, &3));
+    assert!(contacts.is_touching(&4, &1));
+    assert!(!contacts.is_touching(&1, &5));
+}
+
+#[test]
+fn should_begin_continue_and_end_contacts() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    spawn(&engine, 1, 0, A, B);
+    spawn(&engine, 2, 5, B, 0);
+    engine.step();
+    assert_eq!(
+        engine.contacts().of(&1),
+        vec![contact(2, ContactPhase::Begin)]
+    );
+
+    engine.step();
+    assert_eq!(
+        engine.contacts().of(&2),
+        vec![contact(1, ContactPhase::Continue)]
+    );
+
+    engine.remove_entity(2);
+    engine.step();
+    assert_eq!(
+        engine.contacts().of(&1),
+        vec![contact(2, ContactPhase::End)]
+    );
+    assert_eq!(engine.contacts().touching(&1).count(), 0);
+
+    engine.step();
+    assert_eq!(engine.contacts().of(&1), vec![]);
+
+    // contacts are restored on rewind
+    let rewound = engine.engine_at_step(&3, true)?;
+    assert_eq!(
+        rewound.contacts().of(&1),
+        vec![contact(2, ContactPhase::End)]
+    );
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+
+use super::*;
+
+#[test]
+fn should_report_invalid_events() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.register_event(None, spawn_event(1, IVec2::splat(500)));
+    engine.step();
+    assert_eq!(engine.drain_diagnostics(), vec![]);
+
+    engine.register_event(None, spawn_event(1, IVec2::splat(500)));
+    engine.register_event(None, spawn_event(0, IVec2::splat(500)));
+    engine.remove_entity(2);
+    engine.spawn_system(3, TestSystem.into());
+    engine.remove_world_system(4);
+    engine.step();
+    assert_eq!(
+        engine.drain_diagnostics(),
+        vec![
+            (1, EngineDiagnostic::ReplacedEntity { entity_id: 1 }),
+            (1, EngineDiagnostic::SpawnedZeroId),
+            (1, EngineDiagnostic::RemovedMissingEntity { entity_id: 2 }),
+            (
+                1,
+                EngineDiagnostic::SpawnedSystemForMissingEntity { entity_id: 3 }
+            ),
+            (1, EngineDiagnostic::RemovedMissingWorldSystem { id: 4 }),
+        ]
+    );
+    assert_eq!(engine.diagnostics().count(), 0);
+}
+
+#[test]
+fn should_report_rewind() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.step_to(&10);
+    engine
+        .integrate_event(6, spawn_event(1, IVec2::splat(500)))
+        .unwrap();
+    assert_eq!(engine.entity_count(), 1);
+    assert_eq!(
+        engine.drain_diagnostics(),
+        vec![(
+            10,
+            EngineDiagnostic::Rewound {
+                from_step_index: 6,
+                steps: 4
+            }
+        )]
+    );
+}
+
+#[test]
+fn should_not_report_replayed_diagnostics_again() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    // kept and applied again when replaying
+    engine.register_event(None, spawn_event(1, IVec2::splat(500)));
+    engine.step_to(&3);
+    engine.register_event(None, spawn_event(1, IVec2::splat(500)));
+    engine.step_to(&10);
+    assert_eq!(
+        engine.drain_diagnostics(),
+        vec![(3, EngineDiagnostic::ReplacedEntity { entity_id: 1 })]
+    );
+
+    for _ in 0..2 {
+        engine.integrate_event(2, spawn_event(2, IVec2::splat(500)))?;
+    }
+    engine.integrate_event(
+        5,
+        EngineEvent::RemoveEntity {
+            entity_id: 3,
+            is_non_determinism: true,
+        },
+    )?;
+    let rewind = |from_step_index| {
+        (
+            10,
+            EngineDiagnostic::Rewound {
+                from_step_index,
+                steps: 10 - from_step_index,
+            },
+        )
+    };
+    assert_eq!(
+        engine.drain_diagnostics(),
+        vec![
+            rewind(2),
+            rewind(2),
+            (2, EngineDiagnostic::ReplacedEntity { entity_id: 2 }),
+            rewind(5),
+            (5, EngineDiagnostic::RemovedMissingEntity { entity_id: 3 }),
+        ]
+    );
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+
+use super::*;
+
+#[test]
+fn should_not_find_desync_in_matching_engines() -> Result<()> {
+    let mut engine = engine_with_entities(20);
+    let mut other_engine = engine_with_entities(20);
+    engine.step_to(&30);
+    other_engine.step_to(&30);
+    assert_eq!(engine.find_desync(&other_engine)?, None);
+    Ok(())
+}
+
+#[test]
+fn should_find_first_diverging_step() -> Result<()> {
+    let mut engine = engine_with_entities(20);
+    let mut other_engine = engine_with_entities(20);
+    engine.register_event(Some(10), spawn_event(21, IVec2::new(100, 300)));
+    other_engine.register_event(Some(10), spawn_event(21, IVec2::new(200, 300)));
+    other_engine.register_event(Some(10), spawn_event(22, IVec2::splat(100)));
+    engine.step_to(&30);
+    other_engine.step_to(&30);
+
+    // compare against a snapshot of the other engine
+    let snapshot = other_engine.snapshot(blake3::hash(b"game data"));
+    let report = engine.find_desync(snapshot.engine())?.unwrap();
+    // events at step 10 are visible at the end of the step
+    assert_eq!(report.step_index, 11);
+    assert_eq!(report.last_matching_step, Some(10));
+    assert_eq!(
+        report.entities,
+        vec![
+            EntityDiff {
+                id: 21,
+                kind: EntityDiffKind::Changed(vec![FieldDiff {
+                    path: "Test.state.position[0]".to_string(),
+                    local: Some("100".to_string()),
+                    remote: Some("200".to_string()),
+                }]),
+            },
+            EntityDiff {
+                id: 22,
+                kind: EntityDiffKind::OnlyRemote,
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn should_diff_nested_fields() {
+    let local = (vec![1, 2], Some("a"));
+    let remote = (vec![1, 3, 4], None);
+    assert_eq!(
+        diff_fields(&local, &remote),
+        vec![
+            FieldDiff {
+                path: "[0][1]".to_string(),
+                local: Some("2".to_string()),
+                remote: Some("3".to_string()),
+            },
+            FieldDiff {
+                path: "[0][2]".to_string(),
+                local: None,
+                remote: Some("4".to_string()),
+            },
+            FieldDiff {
+                path: "[1]".to_string(),
+                local: Some("\"a\"".to_string()),
+                remote: Some("None".to_string()),
+            },
+        ]
+    );
+}
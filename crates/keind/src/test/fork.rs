@@ -0,0 +1,106 @@
+use anyhow::Result;
+
+use super::*;
+
+/// Moves horizontally by the `push` of its input each step.
+#[crate::entity(TestGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct PushedEntity {}
+
+impl SEEntity<TestGameLogic> for PushedEntity {
+    fn prestep(&self, engine: &GameEngine<TestGameLogic>) -> bool {
+        engine.input_for_entity(&self.id()).push != 0
+    }
+
+    fn step(&self, engine: &GameEngine<TestGameLogic>, next_self: &mut Self) {
+        next_self.state.position.x += engine.input_for_entity(&self.id()).push;
+    }
+}
+
+fn pushed(id: u128) -> EngineEntity {
+    PushedEntity::new(
+        BaseEntityState {
+            id,
+            position: IVec2::splat(500),
+            ..Default::default()
+        },
+        vec![],
+    )
+    .into()
+}
+
+/// An input received from the network.
+fn push_event(entity_id: u128, push: i32) -> EngineEvent<TestGameLogic> {
+    EngineEvent::Input {
+        input: EntityInput { push },
+        entity_id,
+        is_non_determinism: true,
+    }
+}
+
+fn position(engine: &GameEngine<TestGameLogic>, id: u128) -> IVec2 {
+    engine.entity_by_id_untyped(&id, None).unwrap().position()
+}
+
+#[test]
+fn should_fork_with_separate_event_channels() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.spawn_entity(pushed(1));
+    // the pending spawn is copied to the fork
+    let mut fork = engine.fork();
+    fork.spawn_entity(pushed(2));
+    engine.step();
+    fork.step();
+    assert_eq!(engine.entity_count(), 1);
+    assert_eq!(fork.entity_count(), 2);
+
+    // inputs apply from the step after they are registered
+    engine.register_event(None, push_event(1, 10));
+    engine.step_to(&3);
+    fork.step_to(&3);
+    assert_eq!(position(&engine, 1), IVec2::new(510, 500));
+    assert_eq!(position(&fork, 1), IVec2::new(500, 500));
+}
+
+#[test]
+fn should_converge_prediction_with_confirmed_engine() -> Result<()> {
+    let mut confirmed = GameEngine::<TestGameLogic>::default();
+    confirmed.spawn_entity(pushed(1));
+    confirmed.spawn_entity(pushed(2));
+    confirmed.step_to(&10);
+
+    // the local player predicts with its own input, the remote player's
+    // input is not known yet
+    let mut predicted = confirmed.fork();
+    predicted.register_event(Some(12), push_event(1, 5));
+    predicted.step_to(&20);
+    assert_eq!(confirmed.step_index(), &10);
+    assert_eq!(position(&predicted, 1), IVec2::new(535, 500));
+    assert_eq!(position(&predicted, 2), IVec2::new(500, 500));
+
+    // the server confirms both inputs
+    confirmed.register_event(Some(12), push_event(1, 5));
+    confirmed.register_event(Some(14), push_event(2, -3));
+    confirmed.step_to(&20);
+    assert_eq!(position(&confirmed, 2), IVec2::new(485, 500));
+    assert_ne!(predicted.step_hash(&20)?, confirmed.step_hash(&20)?);
+
+    // once the remote input arrives the prediction converges
+    predicted.integrate_event(14, push_event(2, -3))?;
+    for step_index in 1..=20 {
+        assert_eq!(
+            predicted.step_hash(&step_index)?,
+            confirmed.step_hash(&step_index)?,
+            "mismatch at step {step_index}"
+        );
+    }
+
+    // a prediction re-derived from the confirmed engine matches as well
+    let mut rederived = confirmed.fork();
+    rederived.step_to(&30);
+    predicted.step_to(&30);
+    confirmed.step_to(&30);
+    assert_eq!(rederived.step_hash(&30)?, confirmed.step_hash(&30)?);
+    assert_eq!(predicted.step_hash(&30)?, confirmed.step_hash(&30)?);
+    Ok(())
+}
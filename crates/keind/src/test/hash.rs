@@ -0,0 +1,165 @@
+use std::cell::Cell;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::*;
+
+thread_local! {
+    static HASHED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts each time it's serialized, to count how often it's hashed. Moves
+/// by the `push` of its input like `fork::PushedEntity`.
+#[crate::entity(TestGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct HashedEntity {
+    #[serde(serialize_with = "count_hash")]
+    hashed: (),
+}
+
+fn count_hash<S: serde::Serializer>(_: &(), serializer: S) -> Result<S::Ok, S::Error> {
+    HASHED.with(|hashed| hashed.set(hashed.get() + 1));
+    serializer.serialize_unit()
+}
+
+impl SEEntity<TestGameLogic> for HashedEntity {
+    fn prestep(&self, engine: &GameEngine<TestGameLogic>) -> bool {
+        engine.input_for_entity(&self.id()).push != 0
+    }
+
+    fn step(&self, engine: &GameEngine<TestGameLogic>, next_self: &mut Self) {
+        next_self.state.position.x += engine.input_for_entity(&self.id()).push;
+    }
+}
+
+#[test]
+fn should_only_hash_changed_entities() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    for id in 1..=5 {
+        let state = BaseEntityState {
+            id,
+            ..Default::default()
+        };
+        engine.spawn_entity(HashedEntity::new(state, vec![]).into());
+    }
+    engine.step();
+    // serializations in one hash
+    HASHED.with(|hashed| hashed.set(0));
+    GameEngine::<TestGameLogic>::hash_entity(engine.entity_by_id_untyped(&1, None).unwrap());
+    let per_hash = HASHED.with(|hashed| hashed.replace(0));
+    assert_ne!(per_hash, 0);
+
+    engine.step_to(&20);
+    for step_index in 1..=20 {
+        engine.step_hash(&step_index)?;
+    }
+    assert_eq!(HASHED.with(Cell::get), 0);
+
+    // entity 1 moves in the one step its input is set
+    engine.register_event(
+        None,
+        EngineEvent::Input {
+            input: EntityInput { push: 1 },
+            entity_id: 1,
+            is_non_determinism: true,
+        },
+    );
+    engine.step();
+    engine.register_event(
+        None,
+        EngineEvent::Input {
+            input: EntityInput { push: 0 },
+            entity_id: 1,
+            is_non_determinism: true,
+        },
+    );
+    engine.step_to(&30);
+    for step_index in 1..=30 {
+        engine.step_hash(&step_index)?;
+    }
+    let entity = engine.entity_by_id_untyped(&1, None).unwrap();
+    assert_eq!(entity.position().x, 1);
+    assert_eq!(HASHED.with(Cell::get), per_hash);
+    Ok(())
+}
+
+#[test]
+fn should_match_recomputed_step_hashes() -> Result<()> {
+    let mut engine = engine_with_entities(20);
+    engine.step_to(&50);
+    for step_index in 1..50 {
+        let mut hasher = blake3::Hasher::new();
+        for (id, entity) in engine.entities_at_step(&step_index) {
+            hasher.update(&id.to_le_bytes());
+            hasher.update(GameEngine::<TestGameLogic>::hash_entity(entity).as_bytes());
+        }
+        assert_eq!(engine.step_hash(&step_index)?, hasher.finalize());
+    }
+    Ok(())
+}
+
+#[test]
+fn should_find_diverging_entities() -> Result<()> {
+    let mut engine = engine_with_entities(20);
+    let mut other_engine = engine_with_entities(20);
+    // entity 21 is spawned at a different position in each engine
+    engine.register_event(Some(10), spawn_event(21, IVec2::splat(100)));
+    other_engine.register_event(Some(10), spawn_event(21, IVec2::splat(200)));
+    // entity 22 only exists in the other engine
+    other_engine.register_event(Some(10), spawn_event(22, IVec2::splat(100)));
+    engine.step_to(&20);
+    other_engine.step_to(&20);
+
+    assert!(engine.diverging_entity_ids(&other_engine, &5)?.is_empty());
+    assert_eq!(engine.step_hash(&5)?, other_engine.step_hash(&5)?);
+    assert_eq!(
+        engine.diverging_entity_ids(&other_engine, &15)?,
+        vec![21, 22]
+    );
+    assert_ne!(engine.step_hash(&15)?, other_engine.step_hash(&15)?);
+    Ok(())
+}
+
+#[test]
+fn should_exclude_presentation_fields() -> Result<()> {
+    let mut engine = engine_with_entities(5);
+    let mut other_engine = engine_with_entities(5);
+    let mut entity = TestEntity::new(
+        BaseEntityState {
+            id: 6,
+            ..Default::default()
+        },
+        vec![],
+    );
+    entity.frame = 3;
+    engine.spawn_entity(entity.clone().into());
+    entity.frame = 7;
+    other_engine.spawn_entity(entity.clone().into());
+    engine.step_to(&10);
+    other_engine.step_to(&10);
+    for step_index in 1..10 {
+        assert_eq!(
+            engine.step_hash(&step_index)?,
+            other_engine.step_hash(&step_index)?
+        );
+    }
+
+    // not sent over the network, recomputed by the receiver
+    let received: TestEntity = bincode::deserialize(&bincode::serialize(&entity)?)?;
+    assert_eq!(received.frame, 0);
+    Ok(())
+}
+
+#[test]
+fn should_hash_current_step_without_history() -> Result<()> {
+    let mut engine = engine_with_entities(20);
+    let mut simple_engine = engine_with_entities(20);
+    simple_engine.trailing_state_len = 0;
+    engine.step_to(&30);
+    simple_engine.step_to(&30);
+    assert_eq!(simple_engine.step_hash(&30)?, engine.step_hash(&30)?);
+    assert!(simple_engine.step_hash(&29).is_err());
+    Ok(())
+}
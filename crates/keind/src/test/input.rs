@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use super::*;
+
+fn input(push: i32) -> EntityInput {
+    EntityInput { push }
+}
+
+fn set_input(engine: &GameEngine<TestGameLogic>, entity_id: u128, push: i32) {
+    engine.register_event(
+        None,
+        EngineEvent::Input {
+            input: input(push),
+            entity_id,
+            is_non_determinism: true,
+        },
+    );
+}
+
+/// Step to `to_step_index` with inputs changing in some steps, returning
+/// the inputs of entities 1 and 2 at the end of each step.
+fn step_with_inputs(
+    engine: &mut GameEngine<TestGameLogic>,
+    to_step_index: u64,
+) -> BTreeMap<u64, (i32, i32)> {
+    let mut out = BTreeMap::new();
+    while engine.step_index() < &to_step_index {
+        let step_index = *engine.step_index();
+        if step_index % 3 == 0 {
+            set_input(engine, 1, step_index as i32);
+        }
+        if step_index % 7 == 0 {
+            // changed twice in the same step, the last wins
+            set_input(engine, 2, -1);
+            set_input(engine, 2, step_index as i32);
+        }
+        engine.step();
+        out.insert(
+            *engine.step_index(),
+            (
+                engine.input_for_entity(&1).push,
+                engine.input_for_entity(&2).push,
+            ),
+        );
+    }
+    out
+}
+
+#[test]
+fn should_look_up_inputs_at_past_steps() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    let expected = step_with_inputs(&mut engine, 30);
+    assert_eq!(engine.input_for_entity_at_step(&0, &1), Some(&input(0)));
+    for (step_index, (push_1, push_2)) in expected {
+        assert_eq!(
+            engine.input_for_entity_at_step(&step_index, &1),
+            Some(&input(push_1))
+        );
+        assert_eq!(
+            engine.input_for_entity_at_step(&step_index, &2),
+            Some(&input(push_2))
+        );
+        let past = engine.engine_at_step(&step_index, false)?;
+        assert_eq!(past.input_for_entity(&1), &input(push_1));
+        assert_eq!(past.input_for_entity(&2), &input(push_2));
+    }
+    assert_eq!(engine.input_for_entity_at_step(&31, &1), None);
+    Ok(())
+}
+
+#[test]
+fn should_keep_inputs_set_before_the_oldest_step() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.trailing_state_len = 10;
+    let expected = step_with_inputs(&mut engine, 30);
+    // steps 0 to 20 are dropped from the history
+    assert_eq!(engine.input_for_entity_at_step(&20, &1), None);
+    for (step_index, (push_1, push_2)) in expected.range(21..) {
+        assert_eq!(
+            engine.input_for_entity_at_step(step_index, &1),
+            Some(&input(*push_1))
+        );
+        assert_eq!(
+            engine.input_for_entity_at_step(step_index, &2),
+            Some(&input(*push_2))
+        );
+    }
+}
+
+#[test]
+fn should_forget_inputs_after_the_rewound_step() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    step_with_inputs(&mut engine, 20);
+    let mut past = engine.engine_at_step(&10, true)?;
+    assert_eq!(past.input_for_entity_at_step(&11, &1), None);
+    // the pending inputs are replayed
+    past.step_to(&20);
+    for step_index in 0..=20 {
+        assert_eq!(
+            past.input_for_entity_at_step(&step_index, &1),
+            engine.input_for_entity_at_step(&step_index, &1)
+        );
+        assert_eq!(
+            past.input_for_entity_at_step(&step_index, &2),
+            engine.input_for_entity_at_step(&step_index, &2)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn should_look_up_inputs_across_checkpoints() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.trailing_state_len = 100;
+    let expected = step_with_inputs(&mut engine, 200);
+    let mut rewound = engine.engine_at_step(&145, true)?;
+    rewound.step_to(&200);
+    for (step_index, (push_1, push_2)) in expected.range(101..) {
+        for engine in [&engine, &rewound] {
+            assert_eq!(
+                engine.input_for_entity_at_step(step_index, &1),
+                Some(&input(*push_1))
+            );
+            assert_eq!(
+                engine.input_for_entity_at_step(step_index, &2),
+                Some(&input(*push_2))
+            );
+        }
+        let past = engine.engine_at_step(step_index, false)?;
+        assert_eq!(past.input_for_entity(&1), &input(*push_1));
+        assert_eq!(past.input_for_entity(&2), &input(*push_2));
+    }
+    Ok(())
+}
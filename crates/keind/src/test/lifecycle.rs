@@ -0,0 +1,95 @@
+use anyhow::Result;
+
+use super::*;
+
+#[crate::entity(TestGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleEntity {
+    // id of an entity to spawn when this one is removed
+    pub child_id: Option<u128>,
+}
+
+impl SEEntity<TestGameLogic> for LifecycleEntity {
+    fn on_spawn(&self, engine: &GameEngine<TestGameLogic>) {
+        assert!(engine.entity_by_id_untyped(&self.id(), None).is_some());
+        engine.register_game_event(GameEvent::Spawned(self.id()));
+    }
+
+    fn on_despawn(&self, engine: &GameEngine<TestGameLogic>) {
+        assert!(engine.entity_by_id_untyped(&self.id(), None).is_none());
+        engine.register_game_event(GameEvent::Despawned(self.id()));
+        if let Some(child_id) = self.child_id {
+            engine.spawn_entity(lifecycle_entity(child_id, None));
+        }
+    }
+}
+
+fn lifecycle_entity(id: u128, child_id: Option<u128>) -> EngineEntity {
+    let mut entity = LifecycleEntity::new(
+        BaseEntityState {
+            id,
+            ..Default::default()
+        },
+        vec![],
+    );
+    entity.child_id = child_id;
+    entity.into()
+}
+
+fn events(events: Vec<RefPointer<GameEvent>>) -> Vec<GameEvent> {
+    events.into_iter().map(|event| (*event).clone()).collect()
+}
+
+#[test]
+fn should_call_lifecycle_hooks() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.spawn_entity(lifecycle_entity(1, Some(3)));
+    engine.spawn_entity(lifecycle_entity(2, None));
+    assert_eq!(
+        events(engine.step()),
+        vec![GameEvent::Spawned(1), GameEvent::Spawned(2)]
+    );
+    assert_eq!(events(engine.step()), vec![]);
+
+    engine.remove_entity(1);
+    // removing a missing entity does not call on_despawn
+    engine.remove_entity(4);
+    assert_eq!(events(engine.step()), vec![GameEvent::Despawned(1)]);
+    // the child spawned by on_despawn is applied in the next step
+    assert!(engine.entity_by_id_untyped(&3, None).is_none());
+    assert_eq!(events(engine.step()), vec![GameEvent::Spawned(3)]);
+    assert!(engine.entity_by_id_untyped(&3, None).is_some());
+}
+
+#[test]
+fn should_call_lifecycle_hooks_on_replay() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.spawn_entity(lifecycle_entity(1, Some(2)));
+    engine.step_to(&20);
+
+    // remove the entity in the past
+    engine.integrate_event(
+        5,
+        EngineEvent::RemoveEntity {
+            entity_id: 1,
+            is_non_determinism: true,
+        },
+    )?;
+    assert!(engine.entity_by_id_untyped(&1, None).is_none());
+    assert!(engine.entity_by_id_untyped(&2, None).is_some());
+    let expected = vec![
+        GameEvent::Spawned(1),
+        GameEvent::Despawned(1),
+        GameEvent::Spawned(2),
+    ];
+    assert_eq!(events(engine.game_events(0, 20)), expected);
+
+    // replaying again calls the hooks identically
+    let mut replayed = engine.engine_at_step(&3, true)?;
+    replayed.step_to(&20);
+    assert_eq!(events(replayed.game_events(0, 20)), expected);
+    for i in 1..20 {
+        assert_eq!(engine.step_hash(&i)?, replayed.step_hash(&i)?);
+    }
+    Ok(())
+}
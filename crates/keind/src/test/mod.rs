@@ -7,13 +7,43 @@ use serde::Serialize;
 
 use crate::prelude::*;
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct EntityInput {}
+mod constraint;
+mod contact;
+mod diagnostic;
+mod diff;
+mod fork;
+mod hash;
+mod input;
+mod lifecycle;
+mod motion;
+mod parallel;
+mod phases;
+mod remote;
+mod replay;
+mod rewind;
+mod snapshot;
+mod spatial;
+mod world;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GameEvent {}
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityInput {
+    // horizontal movement per step, see `fork::PushedEntity`
+    pub push: i32,
+}
 
-entity_struct!(TestGameLogic, pub struct TestEntity {});
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    Stepped(u128, SystemPhase),
+    Spawned(u128),
+    Despawned(u128),
+}
+
+#[crate::entity(TestGameLogic)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct TestEntity {
+    #[entity(presentation)]
+    pub frame: u64,
+}
 
 impl SEEntity<TestGameLogic> for TestEntity {}
 
@@ -52,11 +82,28 @@ impl EEntitySystem<TestGameLogic> for TestSystem {
 #[derive(Clone, Debug, Serialize, Deserialize, EngineEntity)]
 pub enum EngineEntity {
     Test(TestEntity),
+    Lifecycle(lifecycle::LifecycleEntity),
+    #[systems(require(constraint::MarkerSystem), forbid(TestSystem))]
+    Constrained(constraint::ConstrainedEntity),
+    Pushed(fork::PushedEntity),
+    Hashed(hash::HashedEntity),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, EntitySystem)]
 pub enum EngineEntitySystem {
     Test(TestSystem),
+    Ordered(phases::OrderedSystem),
+    Emitter(parallel::EmitterSystem),
+    #[systems(unique)]
+    Marker(constraint::MarkerSystem),
+    #[systems(require(constraint::MarkerSystem))]
+    NeedsMarker(constraint::NeedsMarkerSystem),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, WorldSystem)]
+pub enum EngineWorldSystem {
+    Spawner(world::SpawnerSystem),
+    Watcher(world::WatcherSystem),
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -64,6 +111,7 @@ pub struct TestGameLogic {}
 impl GameLogic for TestGameLogic {
     type Entity = EngineEntity;
     type System = EngineEntitySystem;
+    type WorldSystem = EngineWorldSystem;
     type Event = GameEvent;
     type Input = EntityInput;
 
@@ -72,6 +120,53 @@ impl GameLogic for TestGameLogic {
         game_events: &Vec<RefPointer<Self::Event>>,
     ) {
     }
+
+    fn validate_remote_event(
+        engine: &GameEngine<Self>,
+        sender: &u128,
+        step_index: &u64,
+        event: &EngineEvent<Self>,
+    ) -> RemoteEventVerdict<Self> {
+        remote::validate_remote_event(engine, sender, step_index, event)
+    }
+}
+
+/// Spawn a `TestEntity` at `position` stepped by `systems`.
+pub fn spawn_entity_event(
+    id: u128,
+    position: IVec2,
+    systems: Vec<RefPointer<EngineEntitySystem>>,
+) -> EngineEvent<TestGameLogic> {
+    EngineEvent::SpawnEntity {
+        entity: RefPointer::new(
+            TestEntity::new(
+                BaseEntityState {
+                    id,
+                    position,
+                    size: IVec2::new(10, 10),
+                    ..Default::default()
+                },
+                systems,
+            )
+            .into(),
+        ),
+        is_non_determinism: true,
+    }
+}
+
+/// Spawn a `TestEntity` moving randomly with a `TestSystem`.
+pub fn spawn_event(id: u128, position: IVec2) -> EngineEvent<TestGameLogic> {
+    spawn_entity_event(id, position, vec![RefPointer::new(TestSystem.into())])
+}
+
+/// An engine spawning entities `1..=count` of `spawn_event` in its first
+/// step.
+pub fn engine_with_entities(count: u128) -> GameEngine<TestGameLogic> {
+    let engine = GameEngine::<TestGameLogic>::default();
+    for id in 1..=count {
+        engine.register_event(None, spawn_event(id, IVec2::splat(500)));
+    }
+    engine
 }
 
 #[test]
@@ -0,0 +1,41 @@
+use super::*;
+
+fn displacement_over(velocity: IVec2, steps: usize) -> IVec2 {
+    let mut state = BaseEntityState::default();
+    (0..steps)
+        .map(|_| state.step_displacement(velocity, 60))
+        .sum()
+}
+
+#[test]
+fn should_move_slower_than_one_unit_per_step() {
+    assert_eq!(displacement_over(IVec2::new(30, 1), 60), IVec2::new(30, 1));
+    assert_eq!(
+        displacement_over(IVec2::new(-45, -1), 60),
+        IVec2::new(-45, -1)
+    );
+}
+
+#[test]
+fn should_not_quantize_speed() {
+    assert_eq!(
+        displacement_over(IVec2::new(250, -350), 60),
+        IVec2::new(250, -350)
+    );
+    assert_eq!(
+        displacement_over(IVec2::new(250, -350), 6),
+        IVec2::new(25, -35)
+    );
+}
+
+#[test]
+fn should_drop_remainder_at_rest() {
+    let mut state = BaseEntityState::default();
+    state.step_displacement(IVec2::new(59, 59), 60);
+    assert_eq!(state.position_remainder, IVec2::new(59, 59));
+    assert_eq!(
+        state.step_displacement(IVec2::new(0, 1), 60),
+        IVec2::new(0, 1)
+    );
+    assert_eq!(state.position_remainder, IVec2::new(0, 0));
+}
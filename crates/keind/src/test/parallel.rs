@@ -0,0 +1,86 @@
+use super::*;
+
+/// Registers an engine event and a game event each step, so the order
+/// entities step in is visible in the event history.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmitterSystem {
+    phase: SystemPhase,
+}
+
+impl EEntitySystem<TestGameLogic> for EmitterSystem {
+    fn phase(&self) -> SystemPhase {
+        self.phase
+    }
+
+    fn prestep(
+        &self,
+        engine: &GameEngine<TestGameLogic>,
+        entity: &<TestGameLogic as GameLogic>::Entity,
+    ) -> bool {
+        engine.register_game_event(GameEvent::Stepped(entity.id(), self.phase));
+        engine.register_event(
+            None,
+            EngineEvent::Input {
+                input: EntityInput::default(),
+                entity_id: entity.id(),
+                is_non_determinism: false,
+            },
+        );
+        false
+    }
+}
+
+#[cfg(feature = "parallel")]
+fn run(parallel: bool) -> (GameEngine<TestGameLogic>, Vec<Vec<u8>>) {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.parallel = parallel;
+    for _ in 0..200 {
+        let entity = TestEntity::new(
+            BaseEntityState {
+                id: engine.generate_id(),
+                position: engine.size() / IVec2::splat(2),
+                ..Default::default()
+            },
+            vec![
+                RefPointer::new(
+                    EmitterSystem {
+                        phase: SystemPhase::Cleanup,
+                    }
+                    .into(),
+                ),
+                RefPointer::new(TestSystem.into()),
+                RefPointer::new(
+                    EmitterSystem {
+                        phase: SystemPhase::PrePhysics,
+                    }
+                    .into(),
+                ),
+            ],
+        );
+        engine.spawn_entity(entity.into());
+    }
+    let mut game_events = Vec::new();
+    for _ in 0..30 {
+        let events = engine.step();
+        game_events.push(bincode::serialize(&events).unwrap());
+    }
+    (engine, game_events)
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn should_step_in_parallel_deterministically() -> Result<()> {
+    let (serial, serial_game_events) = run(false);
+    let (parallel, parallel_game_events) = run(true);
+    for i in 1..=30 {
+        assert_eq!(serial.step_hash(&i)?, parallel.step_hash(&i)?, "step {i}");
+    }
+    assert_eq!(serial_game_events, parallel_game_events);
+    // includes the engine event history
+    let game_data_hash = blake3::hash(b"game data");
+    assert_eq!(
+        serial.snapshot(game_data_hash).to_bytes()?,
+        parallel.snapshot(game_data_hash).to_bytes()?
+    );
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::*;
+
+/// Folds `tag` into the entity x position. The fold doesn't commute so
+/// the final position depends on the order systems ran in.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OrderedSystem {
+    phase: SystemPhase,
+    priority: i32,
+    tag: i32,
+}
+
+impl OrderedSystem {
+    fn fold(x: i32, tag: i32) -> i32 {
+        (x * 3 + tag) % 1000
+    }
+}
+
+impl EEntitySystem<TestGameLogic> for OrderedSystem {
+    fn phase(&self) -> SystemPhase {
+        self.phase
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    fn step(
+        &self,
+        _engine: &GameEngine<TestGameLogic>,
+        _entity: &<TestGameLogic as GameLogic>::Entity,
+        next_entity: &mut <TestGameLogic as GameLogic>::Entity,
+    ) -> Option<Self> {
+        let position = &mut next_entity.state_mut().position;
+        position.x = Self::fold(position.x, self.tag);
+        Some(self.clone())
+    }
+}
+
+fn system(phase: SystemPhase, priority: i32, tag: i32) -> RefPointer<EngineEntitySystem> {
+    RefPointer::new(
+        OrderedSystem {
+            phase,
+            priority,
+            tag,
+        }
+        .into(),
+    )
+}
+
+/// The same systems attached in different orders. Systems 2 and 5 share a
+/// phase and priority so they keep their relative order.
+fn ordered_systems(id: u128) -> Vec<RefPointer<EngineEntitySystem>> {
+    let orders = [[1, 2, 3, 4, 5], [4, 2, 1, 3, 5], [2, 3, 5, 4, 1]];
+    orders[id as usize % orders.len()]
+        .into_iter()
+        .map(|tag| match tag {
+            1 => system(SystemPhase::Cleanup, 0, 1),
+            2 => system(SystemPhase::Physics, 0, 2),
+            3 => system(SystemPhase::PrePhysics, 0, 3),
+            4 => system(SystemPhase::Physics, -1, 4),
+            _ => system(SystemPhase::Physics, 0, 5),
+        })
+        .collect()
+}
+
+#[test]
+fn should_step_systems_by_phase_then_priority() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    for id in 1..=6 {
+        engine.register_event(
+            None,
+            spawn_entity_event(id, IVec2::ZERO, ordered_systems(id)),
+        );
+    }
+    engine.step();
+    engine.step();
+
+    let expected = [3, 4, 2, 5, 1].into_iter().fold(0, OrderedSystem::fold);
+    for id in 1..=6 {
+        let entity = engine.entity_by_id_untyped(&id, None).unwrap();
+        assert_eq!(entity.state().position.x, expected, "entity {id}");
+    }
+    assert_eq!(engine.diagnostics().count(), 0);
+}
+
+#[test]
+fn should_keep_system_order_across_rewind_and_replay() -> Result<()> {
+    let game_data_hash = blake3::hash(b"game data");
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    let mut writer = ReplayWriter::new(Vec::new(), &engine, game_data_hash)?;
+    writer.hash_interval = 5;
+    for id in 1..=10 {
+        let mut events = BTreeMap::new();
+        events.insert(
+            *engine.step_index(),
+            vec![spawn_entity_event(id, IVec2::ZERO, ordered_systems(id))],
+        );
+        writer.record_events(*engine.step_index(), &events)?;
+        engine.integrate_events(events)?;
+        engine.step_to(&(engine.step_index() + 4));
+        writer.record_step_hash(&engine)?;
+    }
+
+    let mut rewound = engine.engine_at_step(&20, true)?;
+    rewound.step_to(engine.step_index());
+    for i in 1..*engine.step_index() {
+        assert_eq!(engine.step_hash(&i)?, rewound.step_hash(&i)?, "step {i}");
+    }
+
+    let replay = Replay::<TestGameLogic>::read(writer.into_inner().as_slice())?;
+    let (replayed, report) = replay.run(&game_data_hash)?;
+    assert!(report.divergence.is_none());
+    assert_eq!(
+        replayed.step_hash(engine.step_index())?,
+        engine.step_hash(engine.step_index())?
+    );
+    Ok(())
+}
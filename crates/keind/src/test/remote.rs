@@ -0,0 +1,104 @@
+use super::*;
+
+/// The most a client may push its entity each step.
+const MAX_PUSH: i32 = 10;
+
+/// Clients may set the input of their own entity, with the push limited
+/// to `MAX_PUSH`.
+pub fn validate_remote_event(
+    engine: &GameEngine<TestGameLogic>,
+    sender: &u128,
+    step_index: &u64,
+    event: &EngineEvent<TestGameLogic>,
+) -> RemoteEventVerdict<TestGameLogic> {
+    if step_index > engine.step_index() {
+        return RemoteEventVerdict::Reject("event is in the future".to_string());
+    }
+    match event {
+        EngineEvent::Input {
+            input,
+            entity_id,
+            is_non_determinism,
+        } if entity_id == sender => {
+            if input.push.abs() <= MAX_PUSH {
+                RemoteEventVerdict::Accept
+            } else {
+                RemoteEventVerdict::Transform(EngineEvent::Input {
+                    input: EntityInput {
+                        push: input.push.clamp(-MAX_PUSH, MAX_PUSH),
+                    },
+                    entity_id: *entity_id,
+                    is_non_determinism: *is_non_determinism,
+                })
+            }
+        }
+        EngineEvent::Input { .. } => {
+            RemoteEventVerdict::Reject("input for another entity".to_string())
+        }
+        _ => RemoteEventVerdict::Reject("clients may only send inputs".to_string()),
+    }
+}
+
+fn input_event(entity_id: u128, push: i32) -> EngineEvent<TestGameLogic> {
+    EngineEvent::Input {
+        input: EntityInput { push },
+        entity_id,
+        is_non_determinism: true,
+    }
+}
+
+fn validate(
+    engine: &GameEngine<TestGameLogic>,
+    sender: u128,
+    step_index: u64,
+    event: &EngineEvent<TestGameLogic>,
+) -> Result<EngineEvent<TestGameLogic>, String> {
+    match TestGameLogic::validate_remote_event(engine, &sender, &step_index, event) {
+        RemoteEventVerdict::Accept => Ok(event.clone()),
+        RemoteEventVerdict::Transform(event) => Ok(event),
+        RemoteEventVerdict::Reject(reason) => Err(reason),
+    }
+}
+
+fn push(event: &EngineEvent<TestGameLogic>) -> i32 {
+    match event {
+        EngineEvent::Input { input, .. } => input.push,
+        _ => panic!("expected an input event"),
+    }
+}
+
+#[test]
+fn should_accept_input_for_own_entity() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.step_to(&5);
+    let event = validate(&engine, 1, 5, &input_event(1, 3)).unwrap();
+    assert_eq!(push(&event), 3);
+    assert_eq!(
+        validate(&engine, 2, 5, &input_event(1, 3)).unwrap_err(),
+        "input for another entity"
+    );
+    assert_eq!(
+        validate(&engine, 1, 6, &input_event(1, 3)).unwrap_err(),
+        "event is in the future"
+    );
+}
+
+#[test]
+fn should_transform_out_of_range_input() {
+    let engine = GameEngine::<TestGameLogic>::default();
+    let event = validate(&engine, 1, 0, &input_event(1, -500)).unwrap();
+    assert_eq!(push(&event), -MAX_PUSH);
+}
+
+#[test]
+fn should_reject_other_events() {
+    let engine = GameEngine::<TestGameLogic>::default();
+    let event = EngineEvent::RemoveEntity {
+        entity_id: 1,
+        is_non_determinism: true,
+    };
+    assert_eq!(
+        validate(&engine, 1, 0, &event).unwrap_err(),
+        "clients may only send inputs"
+    );
+}
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use super::*;
+
+/// Run an engine the way a map instance does, integrating events that
+/// arrive late and recording the whole session.
+fn record_session() -> Result<Vec<u8>> {
+    let game_data_hash = blake3::hash(b"game data");
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    for id in 1..=10 {
+        engine.register_event(None, spawn_event(id, IVec2::splat(500)));
+    }
+    engine.step_to(&20);
+
+    let mut writer = ReplayWriter::new(Vec::new(), &engine, game_data_hash)?;
+    writer.hash_interval = 10;
+    for id in 11..=20 {
+        // each event is a few steps late, forcing a rewind
+        let mut events = BTreeMap::new();
+        events.insert(
+            engine.step_index() - 3,
+            vec![spawn_event(id, IVec2::splat(500))],
+        );
+        writer.record_events(*engine.step_index(), &events)?;
+        engine.integrate_events(events)?;
+        engine.step_to(&(engine.step_index() + 5));
+        writer.record_step_hash(&engine)?;
+    }
+    Ok(writer.into_inner())
+}
+
+#[test]
+fn should_verify_recorded_replay() -> Result<()> {
+    let bytes = record_session()?;
+    let replay = Replay::<TestGameLogic>::read(bytes.as_slice())?;
+    let report = replay.verify(&blake3::hash(b"game data"))?;
+    assert_eq!(report.start_step, 20);
+    assert_eq!(report.end_step, 70);
+    assert_eq!(report.checked_hashes, 5);
+    assert!(report.divergence.is_none());
+    Ok(())
+}
+
+#[test]
+fn should_report_first_divergent_step() -> Result<()> {
+    let bytes = record_session()?;
+    let mut replay = Replay::<TestGameLogic>::read(bytes.as_slice())?;
+    // drop an integration between the first and second recorded hash
+    let index = replay
+        .records
+        .iter()
+        .enumerate()
+        .filter(|(_, record)| matches!(record, ReplayRecord::StepHash { .. }))
+        .map(|(i, _)| i)
+        .next()
+        .unwrap();
+    replay.records.remove(index + 1);
+
+    let report = replay.verify(&blake3::hash(b"game data"))?;
+    let divergence = report.divergence.unwrap();
+    assert_eq!(report.checked_hashes, 2);
+    assert_eq!(divergence.step_index, 40);
+    assert_eq!(divergence.last_matching_step, Some(30));
+    Ok(())
+}
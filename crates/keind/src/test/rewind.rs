@@ -0,0 +1,66 @@
+use anyhow::Result;
+
+use super::*;
+
+#[test]
+fn should_rewind_in_place_like_a_rebuilt_engine() -> Result<()> {
+    let mut engine = engine_with_entities(20);
+    engine.step_to(&100);
+    // rewind by building a past engine and stepping it forward
+    let mut rebuilt = engine.engine_at_step(&40, true)?;
+    rebuilt.register_event(Some(40), spawn_event(21, IVec2::splat(500)));
+    rebuilt.step_to(&100);
+
+    engine.integrate_event(40, spawn_event(21, IVec2::splat(500)))?;
+    assert_eq!(engine.step_index(), &100);
+    for step_index in 1..100 {
+        assert_eq!(
+            engine.step_hash(&step_index)?,
+            rebuilt.step_hash(&step_index)?,
+            "mismatch at step {step_index}"
+        );
+    }
+    engine.step_to(&120);
+    rebuilt.step_to(&120);
+    assert_eq!(engine.step_hash(&120)?, rebuilt.step_hash(&120)?);
+    Ok(())
+}
+
+#[test]
+fn should_keep_pending_events_when_rewinding() -> Result<()> {
+    let mut engine = engine_with_entities(5);
+    engine.step_to(&100);
+    engine.register_event(None, spawn_event(6, IVec2::splat(500)));
+    engine.integrate_event(50, spawn_event(7, IVec2::splat(500)))?;
+    assert_eq!(engine.entity_count(), 6);
+    engine.step();
+    assert_eq!(engine.entity_count(), 7);
+    Ok(())
+}
+
+#[test]
+fn should_reject_events_older_than_history() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.trailing_state_len = 10;
+    for id in 1..=5 {
+        engine.register_event(None, spawn_event(id, IVec2::splat(500)));
+    }
+    engine.step_to(&30);
+    let hash = engine.step_hash(&30)?;
+
+    assert_eq!(
+        engine.integrate_event(5, spawn_event(6, IVec2::splat(500))),
+        Err(IntegrateError::TooLate {
+            step_index: 5,
+            oldest_step_index: 21,
+        })
+    );
+    // the engine is unchanged
+    assert_eq!(engine.step_index(), &30);
+    assert_eq!(engine.step_hash(&30)?, hash);
+    assert_eq!(engine.drain_diagnostics(), vec![]);
+
+    engine.integrate_event(21, spawn_event(6, IVec2::splat(500)))?;
+    assert_eq!(engine.entity_count(), 6);
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anyhow::Result;
+
+use super::*;
+
+#[test]
+fn should_restore_snapshot_deterministically() -> Result<()> {
+    let game_data_hash = blake3::hash(b"game data");
+    let mut engine = engine_with_entities(20);
+    engine.step_to(&30);
+    // queued for a future step
+    engine.register_event(Some(40), spawn_event(21, IVec2::splat(500)));
+    // still waiting in the channel for the next step
+    engine.remove_entity(5);
+
+    let bytes = engine.snapshot(game_data_hash).to_bytes()?;
+    let header = EngineSnapshot::<TestGameLogic>::read_header(&bytes)?;
+    assert_eq!(header.format_version, SNAPSHOT_FORMAT_VERSION);
+    assert_eq!(header.engine_id, *engine.id());
+    assert_eq!(header.step_index, 30);
+    assert_eq!(header.game_data_hash, game_data_hash);
+
+    let mut restored = GameEngine::<TestGameLogic>::from_snapshot(
+        EngineSnapshot::from_bytes(&bytes)?,
+        &game_data_hash,
+    )?;
+    assert_eq!(restored.step_hash(&30)?, engine.step_hash(&30)?);
+
+    engine.step_to(&60);
+    restored.step_to(&60);
+    for step_index in 1..=60 {
+        assert_eq!(
+            engine.step_hash(&step_index)?,
+            restored.step_hash(&step_index)?,
+            "mismatch at step {step_index}"
+        );
+    }
+    assert!(restored.entity_by_id_untyped(&5, None).is_none());
+    assert!(restored.entity_by_id_untyped(&21, None).is_some());
+    Ok(())
+}
+
+#[test]
+fn should_reject_mismatched_game_data() -> Result<()> {
+    let mut engine = engine_with_entities(1);
+    engine.step_to(&5);
+
+    let snapshot = engine.snapshot(blake3::hash(b"game data"));
+    assert!(
+        GameEngine::<TestGameLogic>::from_snapshot(snapshot, &blake3::hash(b"other data")).is_err()
+    );
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use bevy_math::IRect;
+
+use super::*;
+
+/// Spawn `count` randomly moving entities each step for `steps` steps.
+/// Spawns are registered as non-determinism so they survive a rewind.
+fn populated_engine(steps: u64, count: usize) -> GameEngine<TestGameLogic> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    for _ in 0..steps {
+        for _ in 0..count {
+            let entity = TestEntity::new(
+                BaseEntityState {
+                    id: engine.generate_id(),
+                    position: engine.size() / IVec2::splat(2),
+                    size: IVec2::new(20, 20),
+                    ..Default::default()
+                },
+                vec![RefPointer::new(TestSystem.into())],
+            );
+            engine.register_event(
+                None,
+                EngineEvent::SpawnEntity {
+                    entity: RefPointer::new(entity.into()),
+                    is_non_determinism: true,
+                },
+            );
+        }
+        engine.step();
+    }
+    engine
+}
+
+fn brute_force(engine: &GameEngine<TestGameLogic>, rect: &IRect) -> Vec<u128> {
+    engine
+        .entities_by_type::<TestEntity>()
+        .into_iter()
+        .filter(|entity| !entity.rect().intersect(*rect).is_empty())
+        .map(|entity| entity.id())
+        .collect()
+}
+
+fn query_ids(engine: &GameEngine<TestGameLogic>, rect: &IRect) -> Vec<u128> {
+    engine
+        .entities_by_type_in_rect::<TestEntity>(rect)
+        .into_iter()
+        .map(|entity| entity.id())
+        .collect()
+}
+
+fn query_rects() -> Vec<IRect> {
+    vec![
+        IRect::new(0, 0, 1000, 1000),
+        IRect::new(480, 480, 520, 520),
+        IRect::new(500, 500, 501, 501),
+        IRect::new(400, 450, 600, 470),
+        IRect::new(0, 0, 10, 10),
+    ]
+}
+
+#[test]
+fn should_match_brute_force_query() -> Result<()> {
+    let mut engine = populated_engine(20, 10);
+    // build the index, then keep stepping so it is updated incrementally
+    engine.spatial_index();
+    for _ in 0..20 {
+        for rect in query_rects() {
+            assert_eq!(query_ids(&engine, &rect), brute_force(&engine, &rect));
+        }
+        engine.step();
+    }
+    assert_eq!(engine.spatial_index().len(), engine.entity_count());
+    Ok(())
+}
+
+#[test]
+fn should_rebuild_index_after_rewind() -> Result<()> {
+    let engine = populated_engine(60, 5);
+    engine.spatial_index();
+    let mut past_engine = engine.engine_at_step(&30, true)?;
+    past_engine.step_to(engine.step_index());
+    for rect in query_rects() {
+        assert_eq!(query_ids(&engine, &rect), query_ids(&past_engine, &rect));
+    }
+    for i in 31..60 {
+        assert_eq!(engine.step_hash(&i)?, past_engine.step_hash(&i)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn should_remove_entities_from_index() {
+    let mut engine = populated_engine(5, 5);
+    let ids = query_ids(&engine, &IRect::new(0, 0, 1000, 1000));
+    assert_eq!(ids.len(), 25);
+    for id in &ids[..10] {
+        engine.remove_entity(*id);
+    }
+    engine.step();
+    engine.remove_entity_immediate(&ids[10]);
+    let remaining = query_ids(&engine, &IRect::new(0, 0, 1000, 1000));
+    assert_eq!(remaining, ids[11..].to_vec());
+}
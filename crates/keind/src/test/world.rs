@@ -0,0 +1,194 @@
+use anyhow::Result;
+use rand::SeedableRng;
+use rand_xoshiro::Xoroshiro64StarStar;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::*;
+
+/// Spawns an entity every `interval` steps until `remaining` runs out,
+/// then removes itself.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpawnerSystem {
+    interval: u64,
+    remaining: u64,
+    steps_since_spawn: u64,
+}
+
+impl EWorldSystem<TestGameLogic> for SpawnerSystem {
+    fn step(&self, engine: &GameEngine<TestGameLogic>, id: &u128) -> Option<Self> {
+        let mut next = self.clone();
+        next.steps_since_spawn += 1;
+        if next.steps_since_spawn < self.interval {
+            return Some(next);
+        }
+        let mut rng = Xoroshiro64StarStar::seed_from_u64(*id as u64 + engine.step_index());
+        engine.spawn_entity(
+            TestEntity::new(
+                BaseEntityState {
+                    id: rng.random(),
+                    position: IVec2::splat(500),
+                    size: IVec2::new(10, 10),
+                    ..Default::default()
+                },
+                vec![RefPointer::new(TestSystem.into())],
+            )
+            .into(),
+        );
+        next.steps_since_spawn = 0;
+        next.remaining -= 1;
+        if next.remaining == 0 {
+            None
+        } else {
+            Some(next)
+        }
+    }
+}
+
+/// Records the position of an entity each step.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatcherSystem {
+    entity_id: u128,
+    seen: Option<IVec2>,
+}
+
+impl EWorldSystem<TestGameLogic> for WatcherSystem {
+    fn step(&self, engine: &GameEngine<TestGameLogic>, _id: &u128) -> Option<Self> {
+        Some(Self {
+            entity_id: self.entity_id,
+            seen: engine
+                .entity_by_id_untyped(&self.entity_id, None)
+                .map(|entity| entity.position()),
+        })
+    }
+}
+
+fn spawn_spawner(engine: &mut GameEngine<TestGameLogic>, remaining: u64) -> u128 {
+    let id = engine.generate_id();
+    engine.register_event(
+        None,
+        EngineEvent::SpawnWorldSystem {
+            id,
+            system_ptr: RefPointer::new(
+                SpawnerSystem {
+                    interval: 5,
+                    remaining,
+                    steps_since_spawn: 0,
+                }
+                .into(),
+            ),
+            is_non_determinism: true,
+        },
+    );
+    id
+}
+
+#[test]
+fn should_step_world_system_without_entity() {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    let id = spawn_spawner(&mut engine, 4);
+    engine.step_to(&11);
+    assert_eq!(engine.entity_count(), 2);
+    assert!(engine.world_systems().get(&id).is_some());
+
+    engine.step_to(&30);
+    assert_eq!(engine.entity_count(), 4);
+    assert!(engine.world_systems().is_empty());
+}
+
+#[test]
+fn should_hash_and_rewind_world_systems() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    spawn_spawner(&mut engine, 10);
+    engine.step_to(&3);
+    // no entities yet, only the world system state changes
+    assert_eq!(engine.entity_count(), 0);
+    assert_ne!(engine.step_hash(&2)?, engine.step_hash(&3)?);
+
+    engine.step_to(&40);
+    let mut rewound = engine.engine_at_step(&17, true)?;
+    rewound.step_to(&40);
+    for i in 1..40 {
+        assert_eq!(engine.step_hash(&i)?, rewound.step_hash(&i)?, "step {i}");
+    }
+    assert_eq!(engine.entity_count(), rewound.entity_count());
+    Ok(())
+}
+
+#[test]
+fn should_spawn_world_system_in_the_past() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.step_to(&20);
+
+    // an event from the past forces a rewind and replay
+    engine.integrate_event(
+        8,
+        EngineEvent::SpawnWorldSystem {
+            id: 1,
+            system_ptr: RefPointer::new(
+                SpawnerSystem {
+                    interval: 5,
+                    remaining: 10,
+                    steps_since_spawn: 0,
+                }
+                .into(),
+            ),
+            is_non_determinism: false,
+        },
+    )?;
+    assert_eq!(engine.world_systems().len(), 1);
+    assert_eq!(engine.entity_count(), 2);
+    let mut rewound = engine.engine_at_step(&15, true)?;
+    rewound.step_to(&20);
+    assert_eq!(engine.step_hash(&20)?, rewound.step_hash(&20)?);
+    Ok(())
+}
+
+#[test]
+fn should_read_previous_step_in_world_systems() -> Result<()> {
+    let mut engine = GameEngine::<TestGameLogic>::default();
+    engine.spawn_entity(
+        TestEntity::new(
+            BaseEntityState {
+                id: 1,
+                position: IVec2::splat(500),
+                size: IVec2::new(10, 10),
+                ..Default::default()
+            },
+            vec![RefPointer::new(TestSystem.into())],
+        )
+        .into(),
+    );
+    engine.register_event(
+        None,
+        EngineEvent::SpawnWorldSystem {
+            id: 2,
+            system_ptr: RefPointer::new(
+                WatcherSystem {
+                    entity_id: 1,
+                    seen: None,
+                }
+                .into(),
+            ),
+            is_non_determinism: true,
+        },
+    );
+    engine.step_to(&2);
+    let mut moved = false;
+    for step_index in 3..30 {
+        engine.step();
+        let previous = engine
+            .entity_by_id_untyped(&1, Some(step_index - 1))
+            .unwrap();
+        let current = engine.entity_by_id_untyped(&1, None).unwrap();
+        moved |= previous.position() != current.position();
+        let Some(EngineWorldSystem::Watcher(watcher)) =
+            engine.world_systems().get(&2).map(|system| &**system)
+        else {
+            panic!("missing watcher system");
+        };
+        assert_eq!(watcher.seen, Some(previous.position()), "step {step_index}");
+    }
+    assert!(moved);
+    Ok(())
+}
@@ -0,0 +1,43 @@
+use syn::Attribute;
+use syn::Type;
+use syn::parenthesized;
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+
+/// System constraints declared on an enum variant, e.g.
+/// `#[systems(require(AttachSystem), forbid(GravitySystem), unique)]`.
+#[derive(Default)]
+pub struct SystemConstraints {
+    pub require: Vec<Type>,
+    pub forbid: Vec<Type>,
+    pub unique: bool,
+}
+
+impl SystemConstraints {
+    /// Parse the `systems` attributes of a variant. `unique` is only
+    /// accepted if `allow_unique` is set.
+    pub fn parse(attrs: &[Attribute], allow_unique: bool) -> syn::Result<Self> {
+        let mut out = Self::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("systems")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("require") || meta.path.is_ident("forbid") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let types = Punctuated::<Type, Comma>::parse_terminated(&content)?;
+                    if meta.path.is_ident("require") {
+                        out.require.extend(types);
+                    } else {
+                        out.forbid.extend(types);
+                    }
+                    Ok(())
+                } else if allow_unique && meta.path.is_ident("unique") {
+                    out.unique = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported systems constraint"))
+                }
+            })?;
+        }
+        Ok(out)
+    }
+}
@@ -8,6 +8,8 @@ use syn::Fields;
 use syn::Ident;
 use syn::parse_macro_input;
 
+use crate::constraints::SystemConstraints;
+
 pub fn derive_engine_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let enum_name = &input.ident;
@@ -19,8 +21,16 @@ pub fn derive_engine_entity(input: TokenStream) -> TokenStream {
 
     let mut variant_names = Vec::new();
     let mut variant_types = Vec::new();
+    let mut required = Vec::new();
+    let mut forbidden = Vec::new();
 
     for variant in variants {
+        let constraints = match SystemConstraints::parse(&variant.attrs, false) {
+            Ok(constraints) => constraints,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        required.push(constraints.require);
+        forbidden.push(constraints.forbid);
         let variant_name = &variant.ident;
         let variant_type = match &variant.fields {
             Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
@@ -107,6 +117,22 @@ pub fn derive_engine_entity(input: TokenStream) -> TokenStream {
                     )*
                 }
             }
+
+            fn on_spawn(&self, engine: &#crate_name::prelude::GameEngine<GL>) {
+                match self {
+                    #(
+                        #enum_name::#variant_names(entity) => entity.on_spawn(engine),
+                    )*
+                }
+            }
+
+            fn on_despawn(&self, engine: &#crate_name::prelude::GameEngine<GL>) {
+                match self {
+                    #(
+                        #enum_name::#variant_names(entity) => entity.on_despawn(engine),
+                    )*
+                }
+            }
         }
 
         impl<GL> #crate_name::prelude::EEntity<GL> for #enum_name
@@ -148,12 +174,30 @@ pub fn derive_engine_entity(input: TokenStream) -> TokenStream {
                 }
             }
 
-            fn step_systems(&self, engine: &#crate_name::prelude::GameEngine<GL>, next_self_maybe: &mut ::std::option::Option<<GL as #crate_name::GameLogic>::Entity>) {
+            fn required_systems(&self) -> ::std::vec::Vec<#crate_name::prelude::SystemKind> {
                 match self {
                     #(
                         #enum_name::#variant_names(entity) => {
-                            entity.step_systems(engine, next_self_maybe);
-                        },
+                            let mut out = entity.required_systems();
+                            #(
+                                out.push(#crate_name::prelude::SystemKind::of::<#required>());
+                            )*
+                            out
+                        }
+                    )*
+                }
+            }
+
+            fn forbidden_systems(&self) -> ::std::vec::Vec<#crate_name::prelude::SystemKind> {
+                match self {
+                    #(
+                        #enum_name::#variant_names(entity) => {
+                            let mut out = entity.forbidden_systems();
+                            #(
+                                out.push(#crate_name::prelude::SystemKind::of::<#forbidden>());
+                            )*
+                            out
+                        }
                     )*
                 }
             }
@@ -0,0 +1,191 @@
+use proc_macro::TokenStream;
+use proc_macro_crate::FoundCrate;
+use proc_macro_crate::crate_name;
+use quote::quote;
+use syn::Attribute;
+use syn::Data;
+use syn::DeriveInput;
+use syn::Error;
+use syn::Fields;
+use syn::FieldsNamed;
+use syn::Ident;
+use syn::Path;
+use syn::Type;
+use syn::parse_macro_input;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::token::Comma;
+
+/// Fields added to every entity struct.
+const GENERATED_FIELDS: [&str; 2] = ["state", "systems"];
+
+pub fn entity(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if attr.is_empty() {
+        return Error::new(
+            proc_macro::Span::call_site().into(),
+            "expected the game logic type, e.g. `#[keind::entity(MyGameLogic)]`",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let game_logic = parse_macro_input!(attr as Type);
+    let input = parse_macro_input!(item as DeriveInput);
+    match expand(game_logic, input) {
+        Ok(expanded) => expanded,
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(game_logic: Type, mut input: DeriveInput) -> syn::Result<TokenStream> {
+    let derives = derived_traits(&input.attrs)?;
+    let serializable = derives
+        .iter()
+        .any(|name| name == "Serialize" || name == "Deserialize");
+    let Data::Struct(data) = &mut input.data else {
+        return Err(Error::new(
+            input.ident.span(),
+            "#[keind::entity] can only be used on structs",
+        ));
+    };
+    if let Fields::Unnamed(fields) = &data.fields {
+        return Err(Error::new(
+            fields.span(),
+            "#[keind::entity] structs must have named fields",
+        ));
+    }
+    if let Fields::Unit = data.fields {
+        data.fields = Fields::Named(syn::parse_quote!({}));
+    }
+    let Fields::Named(fields) = &mut data.fields else {
+        unreachable!();
+    };
+    for field in &mut fields.named {
+        let ident = field.ident.as_ref().unwrap();
+        if GENERATED_FIELDS.iter().any(|name| ident == name) {
+            return Err(Error::new(
+                ident.span(),
+                format!("field `{ident}` is generated by #[keind::entity]"),
+            ));
+        }
+        if parse_field_options(&mut field.attrs)?.presentation && serializable {
+            // never serialized, so never hashed, sent, or proven
+            field.attrs.push(syn::parse_quote!(#[serde(skip)]));
+        }
+    }
+
+    let crate_name = match crate_name("keind") {
+        Ok(FoundCrate::Itself) => quote! { crate },
+        Ok(FoundCrate::Name(name)) => {
+            let ident = Ident::new(&name, input.ident.span());
+            quote! { ::#ident }
+        }
+        Err(_) => quote! { ::keind }, // fallback to global path
+    };
+
+    let serde_default = if serializable {
+        quote! { #[serde(default)] }
+    } else {
+        quote! {}
+    };
+    let systems_type = quote! {
+        ::std::vec::Vec<#crate_name::RefPointer<<#game_logic as #crate_name::prelude::GameLogic>::System>>
+    };
+    let generated: FieldsNamed = syn::parse_quote!({
+        #serde_default
+        pub state: #crate_name::prelude::BaseEntityState,
+        #serde_default
+        pub systems: #systems_type,
+    });
+    let user_fields = std::mem::take(&mut fields.named);
+    // the remaining fields of `new` are filled with their defaults
+    let defaults = if user_fields.is_empty() {
+        quote! {}
+    } else {
+        quote! { ..::std::default::Default::default() }
+    };
+    fields.named = generated.named;
+    fields.named.extend(user_fields);
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let constructor = if derives.iter().any(|name| name == "Default") {
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                pub fn new(state: #crate_name::prelude::BaseEntityState, systems: #systems_type) -> Self {
+                    Self {
+                        state,
+                        systems,
+                        #defaults
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        #input
+
+        #constructor
+
+        impl #impl_generics #crate_name::prelude::EEntity<#game_logic> for #name #ty_generics #where_clause {
+            fn systems(&self) -> &#systems_type {
+                &self.systems
+            }
+
+            fn systems_mut(&mut self) -> &mut #systems_type {
+                &mut self.systems
+            }
+
+            fn state(&self) -> &#crate_name::prelude::BaseEntityState {
+                &self.state
+            }
+
+            fn state_mut(&mut self) -> &mut #crate_name::prelude::BaseEntityState {
+                &mut self.state
+            }
+        }
+    }
+    .into())
+}
+
+/// Names of the traits in the `derive` attributes of the struct.
+fn derived_traits(attrs: &[Attribute]) -> syn::Result<Vec<String>> {
+    let mut out = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let paths = attr.parse_args_with(Punctuated::<Path, Comma>::parse_terminated)?;
+        out.extend(paths.iter().filter_map(|path| {
+            path.segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+        }));
+    }
+    Ok(out)
+}
+
+/// Options set on a field with `#[entity(..)]`.
+#[derive(Default)]
+struct FieldOptions {
+    /// The field only affects rendering and is not part of the
+    /// authoritative entity state.
+    presentation: bool,
+}
+
+/// Parse and remove the `entity` attributes of a field.
+fn parse_field_options(attrs: &mut Vec<Attribute>) -> syn::Result<FieldOptions> {
+    let mut out = FieldOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("entity")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("presentation") {
+                out.presentation = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported entity field option, expected `presentation`"))
+            }
+        })?;
+    }
+    attrs.retain(|attr| !attr.path().is_ident("entity"));
+    Ok(out)
+}
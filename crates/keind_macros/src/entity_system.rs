@@ -8,6 +8,8 @@ use syn::Fields;
 use syn::Ident;
 use syn::parse_macro_input;
 
+use crate::constraints::SystemConstraints;
+
 pub fn derive_entity_system(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let enum_name = &input.ident;
@@ -19,8 +21,18 @@ pub fn derive_entity_system(input: TokenStream) -> TokenStream {
 
     let mut variant_names = Vec::new();
     let mut variant_types = Vec::new();
+    let mut required = Vec::new();
+    let mut forbidden = Vec::new();
+    let mut unique = Vec::new();
 
     for variant in variants {
+        let constraints = match SystemConstraints::parse(&variant.attrs, true) {
+            Ok(constraints) => constraints,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        required.push(constraints.require);
+        forbidden.push(constraints.forbid);
+        unique.push(constraints.unique);
         let variant_name = &variant.ident;
         let variant_type = match &variant.fields {
             Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
@@ -88,6 +100,66 @@ pub fn derive_entity_system(input: TokenStream) -> TokenStream {
                 #variant_types: #crate_name::prelude::EEntitySystem<GL>,
             )*
         {
+            fn phase(&self) -> #crate_name::prelude::SystemPhase {
+                match self {
+                    #(
+                        #enum_name::#variant_names(system) => system.phase(),
+                    )*
+                }
+            }
+
+            fn priority(&self) -> i32 {
+                match self {
+                    #(
+                        #enum_name::#variant_names(system) => system.priority(),
+                    )*
+                }
+            }
+
+            fn kind(&self) -> #crate_name::prelude::SystemKind {
+                match self {
+                    #(
+                        #enum_name::#variant_names(system) => system.kind(),
+                    )*
+                }
+            }
+
+            fn unique(&self) -> bool {
+                match self {
+                    #(
+                        #enum_name::#variant_names(system) => #unique || system.unique(),
+                    )*
+                }
+            }
+
+            fn required_systems(&self) -> ::std::vec::Vec<#crate_name::prelude::SystemKind> {
+                match self {
+                    #(
+                        #enum_name::#variant_names(system) => {
+                            let mut out = system.required_systems();
+                            #(
+                                out.push(#crate_name::prelude::SystemKind::of::<#required>());
+                            )*
+                            out
+                        }
+                    )*
+                }
+            }
+
+            fn forbidden_systems(&self) -> ::std::vec::Vec<#crate_name::prelude::SystemKind> {
+                match self {
+                    #(
+                        #enum_name::#variant_names(system) => {
+                            let mut out = system.forbidden_systems();
+                            #(
+                                out.push(#crate_name::prelude::SystemKind::of::<#forbidden>());
+                            )*
+                            out
+                        }
+                    )*
+                }
+            }
+
             fn prestep(&self, engine: &#crate_name::prelude::GameEngine<GL>, entity: &<GL as #crate_name::prelude::GameLogic>::Entity) -> bool {
                 match self {
                     #(
@@ -1,17 +1,57 @@
+mod constraints;
 mod engine_entity;
+mod entity;
 mod entity_system;
+mod world_system;
 
 use proc_macro::TokenStream;
 
 /// A wrapper enum around all potential types of
 /// entites in the game. This allows polymorphism
 /// in the engine.
-#[proc_macro_derive(EngineEntity)]
+///
+/// Variants may declare the systems the entity needs with
+/// `#[systems(require(..), forbid(..))]`.
+#[proc_macro_derive(EngineEntity, attributes(systems))]
 pub fn derive_engine_entity(input: TokenStream) -> TokenStream {
     engine_entity::derive_engine_entity(input)
 }
 
-#[proc_macro_derive(EntitySystem)]
+/// A wrapper enum around all entity systems in the game.
+///
+/// Variants may declare constraints on the entity they are attached to
+/// with `#[systems(require(..), forbid(..), unique)]`.
+#[proc_macro_derive(EntitySystem, attributes(systems))]
 pub fn derive_entity_system(input: TokenStream) -> TokenStream {
     entity_system::derive_entity_system(input)
 }
+
+/// A wrapper enum around all world systems in the game.
+#[proc_macro_derive(WorldSystem)]
+pub fn derive_world_system(input: TokenStream) -> TokenStream {
+    world_system::derive_world_system(input)
+}
+
+/// Make a struct an engine entity for the given `GameLogic`. Adds the
+/// `state` and `systems` fields and implements `EEntity`. A `new`
+/// constructor is added if the struct derives `Default`. Place it above
+/// `derive` so derived traits see the added fields.
+///
+/// Fields marked `#[entity(presentation)]` only affect rendering. They are
+/// not serialized, so they are left out of `step_hash`, network messages
+/// and zk inputs, and are reset to their default when an entity is
+/// received. They should be recomputed in `step`.
+///
+/// ```ignore
+/// #[keind::entity(MyGameLogic)]
+/// #[derive(Default, Debug, Clone, Serialize, Deserialize)]
+/// pub struct CoinEntity {
+///     pub value: u64,
+///     #[entity(presentation)]
+///     pub spin_frame: u8,
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn entity(attr: TokenStream, item: TokenStream) -> TokenStream {
+    entity::entity(attr, item)
+}
@@ -0,0 +1,81 @@
+/// Re-run a replay recorded by a map instance or client and check the
+/// recorded step hashes.
+///
+/// usage:
+///   keind_replay <replay file> [assets dir]
+///   keind_replay diff <replay file> <replay file> [assets dir]
+///
+/// `diff` runs two replays of the same engine, e.g. from the server and a
+/// client, and reports the first step where they diverge along with the
+/// entity fields that differ.
+///
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::Result;
+
+use game_common::prelude::*;
+use keind::prelude::*;
+
+const USAGE: &str = "usage: keind_replay <replay file> [assets dir]
+       keind_replay diff <replay file> <replay file> [assets dir]";
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args.as_slice() {
+        ["diff", local, remote] => diff(local, remote, "./assets"),
+        ["diff", local, remote, assets_dir] => diff(local, remote, assets_dir),
+        [replay_path] => verify(replay_path, "./assets"),
+        [replay_path, assets_dir] => verify(replay_path, assets_dir),
+        _ => anyhow::bail!(USAGE),
+    }
+}
+
+fn read_replay(replay_path: &str) -> Result<Replay<KeindGameLogic>> {
+    let replay = Replay::<KeindGameLogic>::read(BufReader::new(File::open(replay_path)?))?;
+    let header = &replay.snapshot.header;
+    println!(
+        "replaying engine {} from step {} ({} records)",
+        header.engine_id,
+        header.step_index,
+        replay.records.len()
+    );
+    Ok(replay)
+}
+
+fn verify(replay_path: &str, assets_dir: &str) -> Result<()> {
+    let game_data = GameData::load(Path::new(assets_dir))?;
+    let report = read_replay(replay_path)?.verify(&game_data.hash()?)?;
+    println!(
+        "replayed steps {}..{}, checked {} step hashes",
+        report.start_step, report.end_step, report.checked_hashes
+    );
+    if let Some(divergence) = report.divergence {
+        println!("DESYNC: first divergent step {}", divergence.step_index);
+        match divergence.last_matching_step {
+            Some(step_index) => println!("last matching step {step_index}"),
+            None => println!("no earlier step hash matched"),
+        }
+        println!("expected hash {}", divergence.expected);
+        println!("actual hash   {}", divergence.actual);
+        std::process::exit(1);
+    }
+    println!("no divergence found");
+    Ok(())
+}
+
+fn diff(local_path: &str, remote_path: &str, assets_dir: &str) -> Result<()> {
+    let game_data_hash = GameData::load(Path::new(assets_dir))?.hash()?;
+    let (local, _) = read_replay(local_path)?.run(&game_data_hash)?;
+    let (remote, _) = read_replay(remote_path)?.run(&game_data_hash)?;
+    match local.find_desync(&remote)? {
+        Some(report) => {
+            println!("DESYNC: {report}");
+            std::process::exit(1);
+        }
+        None => println!("no divergence found in shared steps"),
+    }
+    Ok(())
+}
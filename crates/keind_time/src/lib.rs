@@ -1,8 +1,58 @@
-use std::time::SystemTime;
-use std::time::UNIX_EPOCH;
+use std::sync::Arc;
 
 use keind::prelude::*;
 
+mod clock;
+mod sync;
+#[cfg(test)]
+mod test;
+
+pub use clock::Clock;
+pub use clock::ManualClock;
+pub use clock::ScaledClock;
+pub use clock::SystemClock;
+pub use sync::ClockSync;
+pub use sync::PingSample;
+
+/// Remote steps further than this many seconds away are jumped to instead
+/// of converged on.
+const SNAP_DISTANCE_S: f64 = 0.5;
+/// Seconds over which a distance to a remote engine is corrected.
+const CONVERGE_S: f64 = 1.0;
+/// The most the step rate is sped up or slowed down while converging.
+const MAX_RATE_ADJUST: f64 = 0.1;
+
+/// How far `GameEngineTime::tick` steps an engine that is behind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatchUpPolicy {
+    /// The most steps taken in a single tick. Catching up on more steps
+    /// is spread over the following ticks.
+    pub max_steps_per_tick: u64,
+    /// An engine more steps behind than this isn't stepped, it should be
+    /// resynced instead of fast forwarded.
+    pub resync_distance: u64,
+}
+
+impl Default for CatchUpPolicy {
+    fn default() -> Self {
+        Self {
+            max_steps_per_tick: 30,
+            resync_distance: 600,
+        }
+    }
+}
+
+/// The result of `GameEngineTime::tick`.
+#[must_use]
+pub struct EngineTick<G: GameLogic> {
+    pub game_events: Vec<RefPointer<G::Event>>,
+    /// Steps the engine is still behind, caught up on in later ticks.
+    pub behind: u64,
+    /// The engine is more than `CatchUpPolicy::resync_distance` steps
+    /// behind and wasn't stepped.
+    pub needs_resync: bool,
+}
+
 /// Operate a keind instance at a certain speed
 /// through time. e.g. 60 steps per second
 ///
@@ -10,34 +60,59 @@ use keind::prelude::*;
 /// :inf: steps per second.
 #[derive(Debug, Clone)]
 pub struct GameEngineTime {
-    pub start_timestamp: f64,
+    clock: Arc<dyn Clock>,
+    /// Timestamp the engine was at `anchor_step`.
+    anchor_timestamp: f64,
+    /// Fractional step at `anchor_timestamp`.
+    anchor_step: f64,
     pub steps_per_second: u64,
+    pub catch_up: CatchUpPolicy,
+    /// Multiplier on `steps_per_second`. Adjusted by `sync` to converge on
+    /// a remote engine without jumping steps.
+    rate: f64,
+    /// Multiplier on the speed of time, for slowing down or speeding up
+    /// an engine while debugging.
+    time_scale: f64,
+    /// The engine stays at `anchor_step` while paused.
+    paused: bool,
 }
 
 impl Default for GameEngineTime {
     fn default() -> Self {
-        Self {
-            start_timestamp: Self::now(),
-            steps_per_second: 60,
-        }
+        Self::from_step(0, 60)
     }
 }
 
 impl GameEngineTime {
     pub fn from_step(step_index: u64, steps_per_second: u64) -> Self {
-        let step_len = 1.0 / steps_per_second as f64;
         Self {
-            start_timestamp: Self::now() - (step_index as f64) * step_len,
+            clock: Arc::new(SystemClock),
+            anchor_timestamp: Self::now(),
+            anchor_step: step_index as f64,
             steps_per_second,
+            catch_up: CatchUpPolicy::default(),
+            rate: 1.0,
+            time_scale: 1.0,
+            paused: false,
         }
     }
 
+    /// Read time from `clock`, staying at the current step at the time of
+    /// the clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.anchor_timestamp = clock.now();
+        self.clock = clock;
+        self
+    }
+
     /// Get unix timestamp as a `f64`.
     pub fn now() -> f64 {
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64()
+        SystemClock.now()
+    }
+
+    /// The current time of the clock of this instance.
+    pub fn timestamp(&self) -> f64 {
+        self.clock.now()
     }
 
     #[inline]
@@ -45,22 +120,139 @@ impl GameEngineTime {
         1.0 / (self.steps_per_second as f64)
     }
 
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn time_scale(&self) -> f64 {
+        self.time_scale
+    }
+
+    /// Run `time_scale` times as fast, e.g. 0.5 for half speed.
+    pub fn set_time_scale(&mut self, time_scale: f64) {
+        self.anchor(self.timestamp());
+        self.time_scale = time_scale;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stop at the current step until `resume` is called.
+    pub fn pause(&mut self) {
+        self.anchor(self.timestamp());
+        self.paused = true;
+    }
+
+    /// Continue from the step the engine was paused at.
+    pub fn resume(&mut self) {
+        self.anchor_timestamp = self.timestamp();
+        self.paused = false;
+    }
+
+    /// Continue from `step_index` at the current time, e.g. to drop the
+    /// steps an engine is too far behind to catch up on.
+    pub fn rebase(&mut self, step_index: u64) {
+        self.anchor_step = step_index as f64;
+        self.anchor_timestamp = self.timestamp();
+        self.rate = 1.0;
+    }
+
+    /// Move a paused engine forward by a single step, taken on the next
+    /// `tick`.
+    pub fn step_once(&mut self) {
+        self.anchor(self.timestamp());
+        self.anchor_step = self.anchor_step.floor() + 1.0;
+    }
+
+    /// Steps per second of the clock, including rate adjustments and the
+    /// time scale, ignoring pauses.
+    fn speed(&self) -> f64 {
+        self.steps_per_second as f64 * self.rate * self.time_scale
+    }
+
+    /// Move the anchor to `timestamp`, keeping the current position.
+    fn anchor(&mut self, timestamp: f64) {
+        self.anchor_step = self.step_position_at(timestamp);
+        self.anchor_timestamp = timestamp;
+    }
+
+    /// The fractional step the engine should be at at `timestamp`.
+    pub fn step_position_at(&self, timestamp: f64) -> f64 {
+        if self.paused {
+            return self.anchor_step;
+        }
+        self.anchor_step + (timestamp - self.anchor_timestamp) * self.speed()
+    }
+
+    /// The timestamp the engine reaches `step_index` at, if it isn't
+    /// paused.
+    pub fn step_timestamp(&self, step_index: u64) -> f64 {
+        self.anchor_timestamp + (step_index as f64 - self.anchor_step) / self.speed()
+    }
+
     /// Calculate the step the engine should be at assuming
     /// it steps `self.steps_per_second` times per second.
     pub fn expected_step_index(&self) -> u64 {
-        let uptime = Self::now() - self.start_timestamp;
-        (uptime / self.step_len()).floor() as u64
+        self.expected_step_index_at(self.timestamp())
     }
 
-    /// Tick a game engine by stepping it forward to
-    /// `uptime / steps_per_second`.
-    pub fn tick<G: GameLogic>(&self, engine: &mut GameEngine<G>) -> Vec<RefPointer<G::Event>> {
-        let to_step = self.expected_step_index();
-        if &to_step <= engine.step_index() {
-            println!("noop tick: your tick rate is too high!");
+    pub fn expected_step_index_at(&self, timestamp: f64) -> u64 {
+        self.step_position_at(timestamp).max(0.0).floor() as u64
+    }
+
+    /// Converge on the remote engine estimated by `clock_sync`, see
+    /// `follow`.
+    pub fn sync(&mut self, clock_sync: &ClockSync, timestamp: f64) {
+        if let Some(position) = clock_sync.remote_step_position(timestamp, self.steps_per_second) {
+            self.follow(position, timestamp);
+        }
+    }
+
+    /// Converge on a remote engine at fractional step `remote_position` at
+    /// `timestamp`. Small distances are corrected by adjusting the step
+    /// rate so steps are never skipped or repeated. Distances over
+    /// `SNAP_DISTANCE_S` are jumped to. A paused engine doesn't follow.
+    pub fn follow(&mut self, remote_position: f64, timestamp: f64) {
+        if self.paused {
+            return;
+        }
+        let steps_per_second = self.steps_per_second as f64;
+        let distance = remote_position - self.step_position_at(timestamp);
+        if distance.abs() > SNAP_DISTANCE_S * steps_per_second {
+            self.anchor_step = remote_position;
+            self.anchor_timestamp = timestamp;
+            self.rate = 1.0;
+            return;
+        }
+        let adjust = distance / (steps_per_second * CONVERGE_S);
+        self.anchor(timestamp);
+        self.rate = 1.0 + adjust.clamp(-MAX_RATE_ADJUST, MAX_RATE_ADJUST);
+    }
+
+    /// Step a game engine toward `expected_step_index`, following
+    /// `catch_up`.
+    pub fn tick<G: GameLogic>(&self, engine: &mut GameEngine<G>) -> EngineTick<G> {
+        let behind = self
+            .expected_step_index()
+            .saturating_sub(*engine.step_index());
+        if behind > self.catch_up.resync_distance {
+            return EngineTick {
+                game_events: vec![],
+                behind,
+                needs_resync: true,
+            };
+        }
+        let steps = behind.min(self.catch_up.max_steps_per_tick);
+        let game_events = if steps == 0 {
             vec![]
         } else {
-            engine.step_to(&to_step)
+            engine.step_to(&(engine.step_index() + steps))
+        };
+        EngineTick {
+            game_events,
+            behind: behind - steps,
+            needs_resync: false,
         }
     }
 }
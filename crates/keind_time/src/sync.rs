@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Ping samples kept for the estimate, older samples are dropped.
+const MAX_SAMPLES: usize = 16;
+/// Samples with a round trip time over this multiple of the median are
+/// ignored. A slow round trip is usually slow in one direction only, which
+/// skews its offset.
+const OUTLIER_RTT_FACTOR: f64 = 1.5;
+
+/// Timestamps of a ping to a remote clock and its reply, as in NTP.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PingSample {
+    /// Local time the ping was sent.
+    pub sent: f64,
+    /// Remote time the ping was received.
+    pub remote_received: f64,
+    /// Remote time the reply was sent.
+    pub remote_sent: f64,
+    /// Local time the reply was received.
+    pub received: f64,
+}
+
+impl PingSample {
+    /// Round trip time, not counting the time the remote took to reply.
+    pub fn rtt(&self) -> f64 {
+        (self.received - self.sent) - (self.remote_sent - self.remote_received)
+    }
+
+    /// Remote time minus local time, assuming the ping and the reply took
+    /// equally long.
+    pub fn offset(&self) -> f64 {
+        ((self.remote_received - self.sent) + (self.remote_sent - self.received)) / 2.0
+    }
+}
+
+/// Estimates where a remote engine is, e.g. the server engine a client
+/// follows, from a step of the remote engine and pings to its clock. See
+/// `GameEngineTime::sync`.
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    samples: VecDeque<PingSample>,
+    /// A step of the remote engine and the remote time it was reached.
+    remote_step: Option<(u64, f64)>,
+    /// Remote time minus local time, assuming `remote_step` arrived
+    /// instantly. Used until a ping completes.
+    received_offset: f64,
+}
+
+impl ClockSync {
+    /// Record that the remote engine reached `step_index` at remote time
+    /// `remote_timestamp`, received at local time `received`.
+    pub fn set_remote_step(&mut self, step_index: u64, remote_timestamp: f64, received: f64) {
+        self.remote_step = Some((step_index, remote_timestamp));
+        self.received_offset = remote_timestamp - received;
+    }
+
+    pub fn add_sample(&mut self, sample: PingSample) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Median round trip time of the samples.
+    pub fn rtt(&self) -> Option<f64> {
+        median(self.samples.iter().map(PingSample::rtt).collect())
+    }
+
+    /// Remote time minus local time. The median offset of the samples,
+    /// ignoring samples with an outlying round trip time.
+    pub fn offset(&self) -> Option<f64> {
+        let max_rtt = self.rtt()? * OUTLIER_RTT_FACTOR;
+        median(
+            self.samples
+                .iter()
+                .filter(|sample| sample.rtt() <= max_rtt)
+                .map(PingSample::offset)
+                .collect(),
+        )
+    }
+
+    /// The fractional step of the remote engine at local time `timestamp`.
+    pub fn remote_step_position(&self, timestamp: f64, steps_per_second: u64) -> Option<f64> {
+        let (step_index, remote_timestamp) = self.remote_step?;
+        let remote_now = timestamp + self.offset().unwrap_or(self.received_offset);
+        Some(step_index as f64 + (remote_now - remote_timestamp) * steps_per_second as f64)
+    }
+}
+
+fn median(mut values: Vec<f64>) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        Some((values[mid - 1] + values[mid]) / 2.0)
+    } else {
+        Some(values[mid])
+    }
+}
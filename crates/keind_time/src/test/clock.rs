@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use crate::*;
+
+fn manual_time(step_index: u64) -> (ManualClock, GameEngineTime) {
+    let clock = ManualClock::new(1000.0);
+    let time = GameEngineTime::from_step(step_index, 60).with_clock(Arc::new(clock.clone()));
+    (clock, time)
+}
+
+#[test]
+fn should_read_time_from_injected_clock() {
+    let (clock, time) = manual_time(10);
+    assert_eq!(time.expected_step_index(), 10);
+    clock.advance(1.0);
+    assert_eq!(time.expected_step_index(), 70);
+    clock.set(999.0);
+    assert_eq!(time.expected_step_index(), 0);
+}
+
+#[test]
+fn should_pause_and_step_once() {
+    let (clock, mut time) = manual_time(0);
+    clock.advance(0.5);
+    time.pause();
+    clock.advance(10.0);
+    assert_eq!(time.expected_step_index(), 30);
+
+    time.step_once();
+    time.step_once();
+    assert_eq!(time.expected_step_index(), 32);
+    assert!(time.is_paused());
+
+    // continues from the paused step
+    time.resume();
+    clock.advance(0.5);
+    assert_eq!(time.expected_step_index(), 62);
+}
+
+#[test]
+fn should_scale_time() {
+    let (clock, mut time) = manual_time(0);
+    clock.advance(1.0);
+    time.set_time_scale(0.5);
+    clock.advance(1.0);
+    assert_eq!(time.expected_step_index(), 90);
+    time.set_time_scale(2.0);
+    clock.advance(1.0);
+    assert_eq!(time.expected_step_index(), 210);
+    assert!((time.step_timestamp(330) - 1004.0).abs() < 1e-9);
+}
+
+#[test]
+fn should_scale_clock() {
+    let clock = ManualClock::new(100.0);
+    let scaled = ScaledClock::new(Arc::new(clock.clone()), 0.25);
+    clock.advance(4.0);
+    assert_eq!(scaled.now(), 101.0);
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+
+use crate::*;
+
+/// A ping sent at local time `sent` to a remote clock `offset` seconds
+/// ahead, taking `to_remote` and `to_local` seconds each way.
+fn ping(sent: f64, offset: f64, to_remote: f64, to_local: f64) -> PingSample {
+    let remote_received = sent + to_remote + offset;
+    PingSample {
+        sent,
+        remote_received,
+        remote_sent: remote_received + 0.001,
+        received: sent + to_remote + 0.001 + to_local,
+    }
+}
+
+#[test]
+fn should_estimate_rtt_and_offset() {
+    let sample = ping(100.0, 5.0, 0.04, 0.04);
+    assert!((sample.rtt() - 0.08).abs() < 1e-9);
+    assert!((sample.offset() - 5.0).abs() < 1e-9);
+
+    // asymmetric trips skew the offset by half the difference
+    let sample = ping(100.0, 5.0, 0.02, 0.06);
+    assert!((sample.offset() - 4.98).abs() < 1e-9);
+}
+
+#[test]
+fn should_ignore_outlying_round_trips() {
+    let mut sync = ClockSync::default();
+    assert_eq!(sync.offset(), None);
+    for i in 0..8 {
+        let jitter = (i % 3) as f64 * 0.002;
+        sync.add_sample(ping(i as f64, 2.0, 0.03 + jitter, 0.03));
+    }
+    // stalled on the way back, reads as a clock 0.4s behind
+    sync.add_sample(ping(8.0, 2.0, 0.03, 0.83));
+    sync.add_sample(ping(9.0, 2.0, 0.03, 0.83));
+    let offset = sync.offset().unwrap();
+    assert!((offset - 2.0).abs() < 0.002, "offset {offset}");
+}
+
+#[test]
+fn should_estimate_remote_step() {
+    let mut sync = ClockSync::default();
+    // the remote was at step 600 at remote time 1010, received at local
+    // time 1000.05
+    sync.set_remote_step(600, 1010.0, 1000.05);
+    // without pings, assume the step arrived instantly
+    let position = sync.remote_step_position(1001.05, 60).unwrap();
+    assert!((position - 660.0).abs() < 1e-6);
+
+    sync.add_sample(ping(1000.5, 10.0, 0.05, 0.05));
+    let position = sync.remote_step_position(1001.05, 60).unwrap();
+    assert!((position - 663.0).abs() < 1e-6);
+}
+
+#[test]
+fn should_converge_without_skipping_steps() {
+    let steps_per_second = 60;
+    // the local engine starts 10 steps behind the remote engine
+    let mut time =
+        GameEngineTime::from_step(90, steps_per_second).with_clock(Arc::new(ManualClock::new(0.0)));
+    let remote = GameEngineTime::from_step(100, steps_per_second)
+        .with_clock(Arc::new(ManualClock::new(0.0)));
+    let mut last_step_index = time.expected_step_index_at(0.0);
+    // frames at 60 fps for 5 seconds, syncing every frame
+    for frame in 1..=300 {
+        let timestamp = frame as f64 / 60.0;
+        time.follow(remote.step_position_at(timestamp), timestamp);
+        let step_index = time.expected_step_index_at(timestamp);
+        assert!(
+            step_index >= last_step_index && step_index - last_step_index <= 2,
+            "frame {frame} stepped from {last_step_index} to {step_index}"
+        );
+        last_step_index = step_index;
+    }
+    let distance = remote.step_position_at(5.0) - time.step_position_at(5.0);
+    assert!(distance.abs() < 0.5, "distance {distance}");
+    assert!((time.rate() - 1.0).abs() < 0.01);
+}
+
+#[test]
+fn should_jump_to_distant_remote_step() {
+    let mut time = GameEngineTime::from_step(0, 60).with_clock(Arc::new(ManualClock::new(0.0)));
+    time.follow(600.0, 1.0);
+    assert_eq!(time.expected_step_index_at(1.0), 600);
+    assert_eq!(time.rate(), 1.0);
+    assert!((time.step_timestamp(660) - 2.0).abs() < 1e-9);
+}
@@ -1,10 +1,14 @@
+#[cfg(feature = "sp1")]
 use std::path::PathBuf;
 
 fn main() -> anyhow::Result<()> {
-    zkpo::sp1::build(
+    // the elf files are committed, only SP1 builds them
+    #[cfg(feature = "sp1")]
+    zkpo_build::sp1::build(
         &["noop".into(), "engine".into()],
         &["zk".into()],
         true,
         Some(&PathBuf::from("elf/")),
-    )
+    )?;
+    Ok(())
 }
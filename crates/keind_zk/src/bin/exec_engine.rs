@@ -1,13 +1,19 @@
+use std::path::Path;
+
 use game_common::prelude::*;
 use keind::prelude::*;
+use keind_zk::*;
 use zkpo::prelude::*;
 
 /// A program that runs the keind game engine
 /// in zk for some number of steps with a few
 /// entities present.
+///
+/// usage:
+///   exec_engine [assets dir]
 fn main() -> anyhow::Result<()> {
-    // TODO: take id as input to program
-    // maybe export this as a lib
+    let assets_dir = std::env::args().nth(1).unwrap_or("./assets".to_string());
+    let game_data_hash = GameData::load(Path::new(&assets_dir))?.hash()?;
     let platform = PlatformEntity::new(
         BaseEntityState {
             id: 1,
@@ -28,23 +34,30 @@ fn main() -> anyhow::Result<()> {
     );
     mob_spawner.spawn_data.max_count = 30;
     mob_spawner.spawn_data.mob_type = 1;
-    let engine_events: Vec<EngineEvent<KeindGameLogic>> = vec![
-        EngineEvent::SpawnEntity {
-            entity: RefPointer::new(platform.into()),
-            is_non_determinism: true,
-        },
-        EngineEvent::SpawnEntity {
-            entity: RefPointer::new(mob_spawner.into()),
-            is_non_determinism: true,
-        },
-    ];
-    let step_count: u64 = 3;
+    let transcript = EngineTranscript::from([(
+        0,
+        vec![
+            EngineEvent::SpawnEntity {
+                entity: RefPointer::new(platform.into()),
+                is_non_determinism: true,
+            },
+            EngineEvent::SpawnEntity {
+                entity: RefPointer::new(mob_spawner.into()),
+                is_non_determinism: true,
+            },
+        ],
+    )]);
+    let mut engine = GameEngine::<KeindGameLogic>::default();
+    engine.trailing_state_len = 0;
+    let input = EngineProgramInput::new(&engine, game_data_hash, transcript, 3)?;
 
-    let program = keind_zk::ZKEngineProgram;
+    let program = ZKEngineProgram;
     println!("Executing zk program...");
-    let exe = program.execute(&bincode::serialize(&(step_count, engine_events))?, None)?;
+    let exe = program.execute(&input.to_bytes()?, None)?;
     println!("Generated argument of execution");
-    let out = program.agent().verify(&*exe)?;
+    let out = EngineProgramOutput::from_bytes(&program.agent().verify(&*exe)?)?;
     println!("Verified argument of execution with output data: {out:?}");
+    assert_eq!(out, run_engine_program(&input)?);
+    println!("Output matches native execution");
     Ok(())
 }
@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use game_common::prelude::*;
+use keind::prelude::*;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Events applied by the engine program, keyed by the step they are
+/// applied in.
+pub type EngineTranscript = BTreeMap<u64, Vec<EngineEvent<KeindGameLogic>>>;
+
+/// Input to the engine program: an engine snapshot and the events to
+/// apply to it until `step_index`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EngineProgramInput {
+    /// The encoded `EngineSnapshot` the engine starts from.
+    pub snapshot: Vec<u8>,
+    /// Hash of the `GameData` the engine runs with. The snapshot must have
+    /// been taken with the same game data.
+    pub game_data_hash: blake3::Hash,
+    pub transcript: EngineTranscript,
+    /// The step the engine is run to.
+    pub step_index: u64,
+}
+
+impl EngineProgramInput {
+    /// Run `engine` from its current step to `step_index`.
+    pub fn new(
+        engine: &GameEngine<KeindGameLogic>,
+        game_data_hash: blake3::Hash,
+        transcript: EngineTranscript,
+        step_index: u64,
+    ) -> Result<Self> {
+        Ok(Self {
+            snapshot: engine.snapshot(game_data_hash).to_bytes()?,
+            game_data_hash,
+            transcript,
+            step_index,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+
+    /// Hash of the encoded snapshot.
+    pub fn initial_state_hash(&self) -> blake3::Hash {
+        blake3::hash(&self.snapshot)
+    }
+
+    pub fn transcript_hash(&self) -> Result<blake3::Hash> {
+        Ok(blake3::hash(&bincode::serialize(&self.transcript)?))
+    }
+}
+
+/// The values committed by the engine program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineProgramOutput {
+    /// See `EngineProgramInput::initial_state_hash`.
+    pub initial_state_hash: blake3::Hash,
+    /// See `EngineProgramInput::game_data_hash`.
+    pub game_data_hash: blake3::Hash,
+    /// See `EngineProgramInput::transcript_hash`.
+    pub transcript_hash: blake3::Hash,
+    /// `GameEngine::step_hash` of the final step.
+    pub final_step_hash: blake3::Hash,
+    /// Hash of the game events emitted in each step, see `hash_game_events`.
+    pub game_events_digest: blake3::Hash,
+}
+
+impl EngineProgramOutput {
+    /// Length of the committed output.
+    pub const LEN: usize = 5 * blake3::OUT_LEN;
+
+    /// The hashes concatenated in field order.
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut out = [0; Self::LEN];
+        for (chunk, hash) in out.chunks_exact_mut(blake3::OUT_LEN).zip([
+            &self.initial_state_hash,
+            &self.game_data_hash,
+            &self.transcript_hash,
+            &self.final_step_hash,
+            &self.game_events_digest,
+        ]) {
+            chunk.copy_from_slice(hash.as_bytes());
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != Self::LEN {
+            anyhow::bail!(
+                "engine program output is {} bytes, expected {}",
+                bytes.len(),
+                Self::LEN
+            );
+        }
+        let mut hashes = bytes
+            .chunks_exact(blake3::OUT_LEN)
+            .map(|chunk| blake3::Hash::from_slice(chunk).unwrap());
+        let mut next = || hashes.next().unwrap();
+        Ok(Self {
+            initial_state_hash: next(),
+            game_data_hash: next(),
+            transcript_hash: next(),
+            final_step_hash: next(),
+            game_events_digest: next(),
+        })
+    }
+}
+
+/// Run the engine program. This is the whole guest program, it runs
+/// natively the same as in zk.
+pub fn run_engine_program(input: &EngineProgramInput) -> Result<EngineProgramOutput> {
+    let header = EngineSnapshot::<KeindGameLogic>::read_header(&input.snapshot)?;
+    if input.step_index < header.step_index {
+        anyhow::bail!(
+            "engine program runs to step {} before the snapshot step {}",
+            input.step_index,
+            header.step_index
+        );
+    }
+    let snapshot = EngineSnapshot::from_bytes(&input.snapshot)?;
+    // the engine keeps the `trailing_state_len` of the snapshot, history
+    // is only needed for rewinds so it can be 0 when proving
+    let mut engine = GameEngine::<KeindGameLogic>::from_snapshot(snapshot, &input.game_data_hash)?;
+    for (step_index, events) in &input.transcript {
+        if !(header.step_index..=input.step_index).contains(step_index) {
+            anyhow::bail!(
+                "transcript event at step {step_index} is outside steps {}..={}",
+                header.step_index,
+                input.step_index
+            );
+        }
+        for event in events {
+            engine.register_event(Some(*step_index), event.clone());
+        }
+    }
+    let mut game_events = blake3::Hasher::new();
+    while engine.step_index() < &input.step_index {
+        let events = engine.step();
+        hash_game_events(&mut game_events, engine.step_index(), &events)?;
+    }
+    Ok(EngineProgramOutput {
+        initial_state_hash: input.initial_state_hash(),
+        game_data_hash: input.game_data_hash,
+        transcript_hash: input.transcript_hash()?,
+        final_step_hash: engine.step_hash(engine.step_index())?,
+        game_events_digest: game_events.finalize(),
+    })
+}
+
+/// Add the game events emitted in `step_index` to a digest. Steps without
+/// events are skipped.
+pub fn hash_game_events(
+    hasher: &mut blake3::Hasher,
+    step_index: &u64,
+    events: &[RefPointer<GameEvent>],
+) -> Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+    hasher.update(&step_index.to_le_bytes());
+    hasher.update(&(events.len() as u64).to_le_bytes());
+    for event in events {
+        hasher.update(blake3::hash(&bincode::serialize(&**event)?).as_bytes());
+    }
+    Ok(())
+}
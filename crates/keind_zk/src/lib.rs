@@ -1,3 +1,16 @@
+mod engine_program;
+#[cfg(all(feature = "mock", not(target_os = "zkvm")))]
+mod mock;
+#[cfg(all(test, not(target_os = "zkvm")))]
+mod test;
+
+pub use engine_program::*;
+#[cfg(all(feature = "mock", not(target_os = "zkvm")))]
+pub use mock::*;
+
+#[cfg(not(any(feature = "sp1", feature = "mock", target_os = "zkvm")))]
+compile_error!("keind_zk needs the `sp1` or `mock` feature to execute programs");
+
 #[cfg(not(target_os = "zkvm"))]
 pub mod zk_programs;
 
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use zkpo::prelude::*;
+
+use crate::ZKEngineProgram;
+use crate::ZKNoopProgram;
+
+static DEFAULT_AGENT: OnceLock<MockAgent> = OnceLock::new();
+
+/// A program run natively from its input, returning the data it commits.
+pub type NativeProgram = fn(&[u8]) -> Result<Vec<u8>>;
+
+/// A `ZKAgent` that runs programs natively instead of proving them, for
+/// testing without a zk toolchain. An argument of execution is the input
+/// and output of a program, verified by running the program again. It
+/// proves nothing to anyone who doesn't run the program themselves.
+#[derive(Default)]
+pub struct MockAgent {
+    programs: HashMap<[u8; 32], NativeProgram>,
+}
+
+impl MockAgent {
+    /// A shared agent running the programs in this crate.
+    pub fn singleton() -> &'static Self {
+        DEFAULT_AGENT.get_or_init(|| {
+            Self::default()
+                .with_program(ZKNoopProgram.elf(), ZKNoopProgram::run_native)
+                .with_program(ZKEngineProgram.elf(), ZKEngineProgram::run_native)
+        })
+    }
+
+    /// Run `program` in place of the program built to `elf`.
+    pub fn with_program(mut self, elf: &[u8], program: NativeProgram) -> Self {
+        self.programs.insert(Self::program_id(elf), program);
+        self
+    }
+
+    /// The id of a program for this agent, the hash of its elf.
+    pub fn program_id(elf: &[u8]) -> [u8; 32] {
+        *blake3::hash(elf).as_bytes()
+    }
+
+    fn run(&self, program_id: &[u8; 32], input: &[u8]) -> Result<Vec<u8>> {
+        let Some(program) = self.programs.get(program_id) else {
+            anyhow::bail!(
+                "no native program with id {}",
+                blake3::Hash::from_bytes(*program_id)
+            );
+        };
+        program(input)
+    }
+}
+
+impl ZKAgent for MockAgent {
+    fn execute(&self, input: &[u8], program: &dyn ZKProgram) -> Result<Box<dyn ZKExe>> {
+        let program_id = Self::program_id(program.elf());
+        let output = self.run(&program_id, input)?;
+        Ok(Box::new(MockExe {
+            program_id,
+            transcript: bincode::serialize(&(input, output))?,
+        }))
+    }
+
+    fn verify(&self, proof: &dyn ZKExe) -> Result<Vec<u8>> {
+        let (input, output): (Vec<u8>, Vec<u8>) = bincode::deserialize(proof.cipher_bytes())?;
+        if self.run(proof.program_id(), &input)? != output {
+            anyhow::bail!(
+                "mock argument of execution for {} does not match the program output",
+                proof.program_name()
+            );
+        }
+        Ok(output)
+    }
+}
+
+/// A mock argument of execution, see `MockAgent`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MockExe {
+    pub program_id: [u8; 32],
+    /// The program input and output, encoded.
+    pub transcript: Vec<u8>,
+}
+
+impl ZKExe for MockExe {
+    fn program_id(&self) -> &[u8; 32] {
+        &self.program_id
+    }
+
+    fn cipher_bytes(&self) -> &[u8] {
+        &self.transcript
+    }
+
+    fn agent(&self) -> &dyn ZKAgent {
+        MockAgent::singleton()
+    }
+
+    fn program(&self) -> Option<&dyn ZKProgram> {
+        None
+    }
+}
@@ -0,0 +1,78 @@
+use anyhow::Result;
+use game_common::prelude::*;
+
+use super::*;
+
+#[test]
+fn should_commit_engine_run() -> Result<()> {
+    let mut engine = engine();
+    let input = EngineProgramInput::new(&engine, game_data_hash(), transcript(), 30)?;
+    let output = run_engine_program(&EngineProgramInput::from_bytes(&input.to_bytes()?)?)?;
+
+    // the same run in an engine keeping its history
+    let mut game_events = blake3::Hasher::new();
+    for (step_index, events) in transcript() {
+        for event in events {
+            engine.register_event(Some(step_index), event);
+        }
+    }
+    while engine.step_index() < &30 {
+        let events = engine.step();
+        hash_game_events(&mut game_events, engine.step_index(), &events)?;
+    }
+    assert_eq!(output.initial_state_hash, input.initial_state_hash());
+    assert_eq!(output.game_data_hash, game_data_hash());
+    assert_eq!(output.transcript_hash, input.transcript_hash()?);
+    assert_eq!(output.final_step_hash, engine.step_hash(&30)?);
+    assert_eq!(output.game_events_digest, game_events.finalize());
+    assert_ne!(output.game_events_digest, blake3::Hasher::new().finalize());
+    Ok(())
+}
+
+#[test]
+fn should_commit_transcript() -> Result<()> {
+    let engine = engine();
+    let input = EngineProgramInput::new(&engine, game_data_hash(), transcript(), 30)?;
+    let output = run_engine_program(&input)?;
+    let mut other_transcript = transcript();
+    other_transcript.remove(&20);
+    let other_input = EngineProgramInput::new(&engine, game_data_hash(), other_transcript, 30)?;
+    let other_output = run_engine_program(&other_input)?;
+
+    assert_eq!(output.initial_state_hash, other_output.initial_state_hash);
+    assert_ne!(output.transcript_hash, other_output.transcript_hash);
+    assert_ne!(output.final_step_hash, other_output.final_step_hash);
+    Ok(())
+}
+
+#[test]
+fn should_reject_events_outside_the_run() -> Result<()> {
+    let engine = engine();
+    for step_index in [5, 31] {
+        let transcript =
+            EngineTranscript::from([(step_index, vec![input_event(EntityInput::default())])]);
+        let input = EngineProgramInput::new(&engine, game_data_hash(), transcript, 30)?;
+        assert!(run_engine_program(&input).is_err());
+    }
+    let input = EngineProgramInput::new(&engine, game_data_hash(), transcript(), 5)?;
+    assert!(run_engine_program(&input).is_err());
+    Ok(())
+}
+
+#[test]
+fn should_reject_snapshot_of_other_game_data() -> Result<()> {
+    let mut input = EngineProgramInput::new(&engine(), game_data_hash(), transcript(), 30)?;
+    input.game_data_hash = blake3::hash(b"other game data");
+    assert!(run_engine_program(&input).is_err());
+    Ok(())
+}
+
+#[test]
+fn should_encode_output() -> Result<()> {
+    let input = EngineProgramInput::new(&engine(), game_data_hash(), transcript(), 30)?;
+    let output = run_engine_program(&input)?;
+    let bytes = output.to_bytes();
+    assert_eq!(EngineProgramOutput::from_bytes(&bytes)?, output);
+    assert!(EngineProgramOutput::from_bytes(&bytes[1..]).is_err());
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use zkpo::prelude::*;
+
+use super::*;
+
+#[test]
+fn should_execute_engine_program() -> Result<()> {
+    let input = EngineProgramInput::new(&engine(), game_data_hash(), transcript(), 30)?;
+    let program = ZKEngineProgram;
+    let exe = program.execute(&input.to_bytes()?, Some(MockAgent::singleton()))?;
+    assert_eq!(exe.program_id(), &MockAgent::program_id(program.elf()));
+    let output = EngineProgramOutput::from_bytes(&MockAgent::singleton().verify(&*exe)?)?;
+    assert_eq!(output, run_engine_program(&input)?);
+
+    let exe = ZKNoopProgram.execute(&[], Some(MockAgent::singleton()))?;
+    assert!(exe.agent().verify(&*exe)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn should_reject_tampered_output() -> Result<()> {
+    let input = EngineProgramInput::new(&engine(), game_data_hash(), transcript(), 30)?;
+    let exe = ZKEngineProgram.execute(&input.to_bytes()?, Some(MockAgent::singleton()))?;
+    let (input, mut output): (Vec<u8>, Vec<u8>) = bincode::deserialize(exe.cipher_bytes())?;
+    output[0] ^= 1;
+    let tampered = MockExe {
+        program_id: *exe.program_id(),
+        transcript: bincode::serialize(&(input, output))?,
+    };
+    assert!(MockAgent::singleton().verify(&tampered).is_err());
+
+    // a valid transcript of another program
+    let noop = ZKNoopProgram.execute(&[], Some(MockAgent::singleton()))?;
+    let tampered = MockExe {
+        program_id: *exe.program_id(),
+        transcript: noop.cipher_bytes().to_vec(),
+    };
+    assert!(MockAgent::singleton().verify(&tampered).is_err());
+    Ok(())
+}
+
+#[test]
+fn should_only_run_known_programs() -> Result<()> {
+    let agent = MockAgent::default();
+    assert!(ZKNoopProgram.execute(&[], Some(&agent)).is_err());
+    let agent = agent.with_program(ZKNoopProgram.elf(), |input| Ok(input.to_vec()));
+    let exe = ZKNoopProgram.execute(&[1, 2, 3], Some(&agent))?;
+    assert_eq!(agent.verify(&*exe)?, vec![1, 2, 3]);
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use db::PlayerRecord;
+use db::PlayerStats;
+use game_common::prelude::*;
+use keind::prelude::*;
+
+use crate::*;
+
+mod engine_program;
+#[cfg(feature = "mock")]
+mod mock;
+
+pub fn game_data_hash() -> blake3::Hash {
+    blake3::hash(b"game data")
+}
+
+/// An engine with a platform and a player standing on it.
+pub fn engine() -> GameEngine<KeindGameLogic> {
+    let mut engine = GameEngine::<KeindGameLogic>::default();
+    let platform = PlatformEntity::new(
+        BaseEntityState {
+            id: 1,
+            position: IVec2::new(0, 0),
+            size: IVec2::new(400, 25),
+            ..Default::default()
+        },
+        vec![],
+    );
+    let player = PlayerEntity::new_with_ids(
+        2,
+        PlayerRecord {
+            current_health: 100,
+            ..Default::default()
+        },
+        PlayerStats::default(),
+    );
+    engine.spawn_entity(platform.into());
+    engine.spawn_entity(player.into());
+    engine.step_to(&10);
+    engine
+}
+
+pub fn input_event(input: EntityInput) -> EngineEvent<KeindGameLogic> {
+    EngineEvent::Input {
+        input,
+        entity_id: 2,
+        is_non_determinism: false,
+    }
+}
+
+pub fn transcript() -> EngineTranscript {
+    EngineTranscript::from([
+        (
+            12,
+            vec![input_event(EntityInput {
+                move_right: true,
+                pick_up: true,
+                ..Default::default()
+            })],
+        ),
+        (20, vec![input_event(EntityInput::default())]),
+    ])
+}
@@ -1,18 +1,25 @@
 use std::sync::OnceLock;
 
-use sp1_sdk::HashableKey;
-use sp1_sdk::ProverClient;
+use anyhow::Result;
 use zkpo::prelude::*;
 
+use crate::EngineProgramInput;
+use crate::run_engine_program;
+
 pub struct ZKEngineProgram;
+
+impl ZKEngineProgram {
+    /// Run the program natively, see `run_engine_program`.
+    pub fn run_native(input: &[u8]) -> Result<Vec<u8>> {
+        let output = run_engine_program(&EngineProgramInput::from_bytes(input)?)?;
+        Ok(output.to_bytes().to_vec())
+    }
+}
+
 impl ZKProgram for ZKEngineProgram {
     fn id(&self) -> &[u8; 32] {
         static HASH: OnceLock<[u8; 32]> = OnceLock::new();
-        HASH.get_or_init(|| {
-            let client = ProverClient::from_env();
-            let (_pk, vk) = client.setup(self.elf());
-            vk.hash_bytes()
-        })
+        HASH.get_or_init(|| super::program_id(self.elf()))
     }
 
     fn elf(&self) -> &[u8] {
@@ -24,6 +31,6 @@ impl ZKProgram for ZKEngineProgram {
     }
 
     fn agent(&self) -> &dyn ZKAgent {
-        ZKSPOneAgent::singleton()
+        super::default_agent()
     }
 }
@@ -1,2 +1,29 @@
 pub mod engine;
 pub mod noop;
+
+use zkpo::prelude::*;
+
+/// Id of the program with `elf` for the default agent.
+#[cfg(feature = "sp1")]
+fn program_id(elf: &[u8]) -> [u8; 32] {
+    use sp1_sdk::HashableKey;
+    use sp1_sdk::ProverClient;
+
+    let client = ProverClient::from_env();
+    let (_pk, vk) = client.setup(elf);
+    vk.hash_bytes()
+}
+
+#[cfg(not(feature = "sp1"))]
+fn program_id(elf: &[u8]) -> [u8; 32] {
+    crate::MockAgent::program_id(elf)
+}
+
+/// The agent programs are executed with unless another is given. Mock
+/// execution is only the default without SP1.
+fn default_agent() -> &'static dyn ZKAgent {
+    #[cfg(feature = "sp1")]
+    return ZKSPOneAgent::singleton();
+    #[cfg(not(feature = "sp1"))]
+    crate::MockAgent::singleton()
+}
@@ -1,18 +1,21 @@
 use std::sync::OnceLock;
 
-use sp1_sdk::HashableKey;
-use sp1_sdk::ProverClient;
+use anyhow::Result;
 use zkpo::prelude::*;
 
 pub struct ZKNoopProgram;
+
+impl ZKNoopProgram {
+    /// Run the program natively, it commits nothing.
+    pub fn run_native(_input: &[u8]) -> Result<Vec<u8>> {
+        Ok(vec![])
+    }
+}
+
 impl ZKProgram for ZKNoopProgram {
     fn id(&self) -> &[u8; 32] {
         static HASH: OnceLock<[u8; 32]> = OnceLock::new();
-        HASH.get_or_init(|| {
-            let client = ProverClient::from_env();
-            let (_pk, vk) = client.setup(self.elf());
-            vk.hash_bytes()
-        })
+        HASH.get_or_init(|| super::program_id(self.elf()))
     }
 
     fn elf(&self) -> &[u8] {
@@ -24,6 +27,6 @@ impl ZKProgram for ZKNoopProgram {
     }
 
     fn agent(&self) -> &dyn ZKAgent {
-        ZKSPOneAgent::singleton()
+        super::default_agent()
     }
 }
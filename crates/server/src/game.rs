@@ -15,6 +15,7 @@ use db::PlayerInventory;
 use db::PlayerStats;
 use game_common::prelude::*;
 use keind::prelude::*;
+use keind_time::GameEngineTime;
 
 use super::MapInstance;
 use super::PlayerRecord;
@@ -68,6 +69,14 @@ impl Game {
             self.game_events.0.clone(),
         )?;
         map_data.init(&self.game_data, &mut map_instance.engine)?;
+        if let Ok(replay_dir) = std::env::var(REPLAY_DIR_ENV) {
+            let path = Path::new(&replay_dir).join(format!(
+                "{}-{}.replay",
+                map_data.name,
+                map_instance.engine.id()
+            ));
+            map_instance.record_replay(&path, self.game_data.hash()?)?;
+        }
         let map_instance = Arc::new(RwLock::new(map_instance));
         self.map_instances
             .insert(map_data.name.to_string(), map_instance.clone());
@@ -208,8 +217,14 @@ impl Game {
     pub async fn handle_action(&self, socket_id: String, action: Action) -> anyhow::Result<()> {
         match action {
             Action::LogoutPlayer => {}
-            Action::Ping => {
-                self.network_server.send(&socket_id, Response::Pong).await?;
+            Action::Ping(sent) => {
+                let received = GameEngineTime::now();
+                self.network_server
+                    .send(
+                        &socket_id,
+                        Response::Pong(sent, received, GameEngineTime::now()),
+                    )
+                    .await?;
             }
             Action::CreatePlayer(_name) => {
                 panic!("not in use");
@@ -12,6 +12,8 @@ use keind_time::GameEngineTime;
 mod game;
 mod map_instance;
 mod network;
+#[cfg(test)]
+mod test;
 
 use map_instance::MapInstance;
 
@@ -1,5 +1,8 @@
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -49,6 +52,13 @@ pub struct MapInstance {
     db: Arc<redb::Database>,
     pub game_events: flume::Sender<GameEvent>,
     latest_processed_game_events: u64,
+
+    /// Number of each kind of engine diagnostic seen by this instance.
+    pub diagnostic_counts: BTreeMap<&'static str, u64>,
+
+    /// Optional recording of everything integrated into the engine,
+    /// for reproducing desyncs with `keind_replay`.
+    replay: Option<ReplayWriter<KeindGameLogic, BufWriter<File>>>,
 }
 
 /// A MapInstance handles communication with the player.
@@ -75,9 +85,32 @@ impl MapInstance {
             db,
             game_events,
             latest_processed_game_events: 0,
+            diagnostic_counts: BTreeMap::new(),
+            replay: None,
         })
     }
 
+    /// Start recording a replay of this instance to `path`. The replay
+    /// begins from the current engine state.
+    pub fn record_replay(&mut self, path: &Path, game_data_hash: blake3::Hash) -> Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        self.replay = Some(ReplayWriter::new(writer, &self.engine, game_data_hash)?);
+        println!("recording replay for {} to {:?}", self.map.name, path);
+        Ok(())
+    }
+
+    /// Pass the result of a replay write through, stopping the recording
+    /// if it fails.
+    fn check_replay(&mut self, result: Result<()>) {
+        if let Err(e) = result {
+            println!(
+                "WARNING: failed to write replay for {}, stopping recording: {e}",
+                self.map.name
+            );
+            self.replay = None;
+        }
+    }
+
     pub async fn spawn_item(&mut self, player_id: &str, item: (u64, u32)) -> Result<()> {
         if let Some(player_engine) = self.player_engines.get(player_id) {
             if let Some(entity) = self
@@ -101,8 +134,7 @@ impl MapInstance {
                 };
                 self.pending_events
                     .0
-                    .send((*self.engine.step_index(), event.clone()))?;
-                self.engine.register_event(None, event);
+                    .send((*self.engine.step_index(), event))?;
             }
         }
         Ok(())
@@ -133,7 +165,6 @@ impl MapInstance {
                 entity_id: last_engine.entity_id,
                 is_non_determinism: true,
             };
-            self.engine.register_event(None, remove.clone());
             self.pending_events
                 .0
                 .send((*self.engine.step_index(), remove))?;
@@ -142,7 +173,6 @@ impl MapInstance {
             entity: RefPointer::new(entity.into()),
             is_non_determinism: true,
         };
-        self.engine.register_event(None, add_event.clone());
         self.pending_events
             .0
             .send((*self.engine.step_index(), add_event))?;
@@ -155,7 +185,6 @@ impl MapInstance {
                 entity_id: player.entity_id,
                 is_non_determinism: true,
             };
-            self.engine.register_event(None, event.clone());
             self.pending_events
                 .0
                 .send((*self.engine.step_index(), event))?;
@@ -201,28 +230,33 @@ impl MapInstance {
         }
 
         // player action validity checks/logic
-        if let Some(player) = self.player_engines.get_mut(player_id) {
-            // check that we're syncing with the correct engine
-            if player.engine_id.is_none() || &player.engine_id.unwrap() != engine_id {
-                // we discard without erroring, wait for engine to be inited
+        let Some(player) = self.player_engines.get_mut(player_id) else {
+            anyhow::bail!("unknown player id, discarding game event");
+        };
+        // check that we're syncing with the correct engine
+        if player.engine_id.is_none() || &player.engine_id.unwrap() != engine_id {
+            // we discard without erroring, wait for engine to be inited
+            return Ok(None);
+        }
+        let event = match KeindGameLogic::validate_remote_event(
+            &self.engine,
+            &player.entity_id,
+            step_index,
+            event,
+        ) {
+            RemoteEventVerdict::Accept => event.clone(),
+            RemoteEventVerdict::Transform(event) => event,
+            RemoteEventVerdict::Reject(reason) => {
+                // discard without erroring, the other events are still valid
+                println!("WARNING: rejected event from player {player_id}: {reason}");
                 return Ok(None);
             }
-            // Structure for validity checks
-            match event {
-                EngineEvent::Input { entity_id, .. } => {
-                    if entity_id != &player.entity_id {
-                        anyhow::bail!("player tried to input for wrong entity");
-                    }
-                    println!("integrating input event at {step_index}");
-                    player.last_input_step_index = *step_index;
-                    return Ok(Some((*step_index, event.clone())));
-                }
-                _ => {} // disallow all others
-            }
-        } else {
-            anyhow::bail!("unknown player id, discarding game event");
+        };
+        if let EngineEvent::Input { .. } = event {
+            println!("integrating input event at {step_index}");
+            player.last_input_step_index = *step_index;
         }
-        Ok(None)
+        Ok(Some((*step_index, event)))
     }
 
     /// ingest an event from a point in time in a another engine within 60 steps of this engine
@@ -248,7 +282,7 @@ impl MapInstance {
         // integrate any events we've received since last tick
         let pending_actions = self.pending_actions.1.drain().collect::<Vec<_>>();
         let pending_events = self.pending_events.1.drain().collect::<Vec<_>>();
-        let has_events = !pending_events.is_empty() || !pending_actions.is_empty();
+        let mut has_events = !pending_events.is_empty() || !pending_actions.is_empty();
         let mut new_events = if pending_actions.len() > 0 {
             self.process_remote_events(pending_actions).await?
         } else {
@@ -258,11 +292,59 @@ impl MapInstance {
             new_events.entry(si).or_default().push(event);
         }
         if has_events {
-            self.engine.integrate_events(new_events.clone());
+            if let Err(IntegrateError::TooLate {
+                oldest_step_index, ..
+            }) = self.engine.integrate_events(new_events.clone())
+            {
+                // drop the events too old to integrate and don't share them
+                // with clients. A client that predicted with them resyncs
+                // once it sees the step hashes diverge
+                let kept = new_events.split_off(&oldest_step_index);
+                println!(
+                    "WARNING: {} dropped {} events older than step {oldest_step_index}",
+                    self.map.name,
+                    new_events.values().map(Vec::len).sum::<usize>()
+                );
+                new_events = kept;
+                self.engine.integrate_events(new_events.clone())?;
+                has_events = !new_events.is_empty();
+            }
+            if let Some(replay) = &mut self.replay {
+                let result = replay.record_events(*self.engine.step_index(), &new_events);
+                self.check_replay(result);
+            }
         }
 
         // step as needed
-        self.engine_time.tick(&mut self.engine);
+        let tick = self.engine_time.tick(&mut self.engine);
+        if tick.needs_resync {
+            // the map stalled for too long to catch up, continue from where
+            // the engine is. Clients are ahead of the map now, send them
+            // the engine again
+            println!(
+                "WARNING: {} is {} steps behind, resyncing players",
+                self.map.name, tick.behind
+            );
+            self.engine_time.rebase(*self.engine.step_index());
+            for player in self.player_engines.values_mut() {
+                player.is_inited = false;
+                player.engine_id = None;
+            }
+        }
+        if let Some(replay) = &mut self.replay {
+            let result = replay.record_step_hash(&self.engine);
+            self.check_replay(result);
+        }
+        for (step_index, diagnostic) in self.engine.drain_diagnostics() {
+            let count = self.diagnostic_counts.entry(diagnostic.kind()).or_default();
+            *count += 1;
+            if !matches!(diagnostic, EngineDiagnostic::Rewound { .. }) {
+                println!(
+                    "WARNING: {} step {step_index}: {diagnostic} ({count} total)",
+                    self.map.name
+                );
+            }
+        }
 
         // process game events at a delayed rate to allow lagged user inputs
         let latest_step = *self.engine.step_index() - STEP_DELAY.min(*self.engine.step_index());
@@ -450,10 +532,7 @@ impl MapInstance {
         }
         for (player_id, e) in removal_events {
             self.player_engines.remove(&player_id);
-            self.pending_events
-                .0
-                .send((*self.engine.step_index(), e.clone()))?;
-            self.engine.register_event(None, e);
+            self.pending_events.0.send((*self.engine.step_index(), e))?;
         }
         Ok(())
     }
@@ -482,10 +561,12 @@ impl MapInstance {
         player.is_inited = true;
         player.engine_id = Some(*client_engine.id());
 
+        let server_step = engine_time.expected_step_index();
         let response = Response::EngineState(
             client_engine,
             player.entity_id,
-            engine_time.expected_step_index(),
+            server_step,
+            engine_time.step_timestamp(server_step),
         );
         let player_id = player_id.to_string();
         network_server.send_to_player(&player_id, response).await;
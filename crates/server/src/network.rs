@@ -33,9 +33,11 @@ pub struct Server {
 
 impl Server {
     pub async fn new() -> Result<Self> {
-        let addr = "0.0.0.0:1351";
-        let try_socket = TcpListener::bind(addr).await;
-        let listener = try_socket.expect("Failed to bind");
+        Self::bind("0.0.0.0:1351").await
+    }
+
+    pub async fn bind(addr: &str) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
 
         Ok(Self {
             pending_actions: flume::unbounded(),
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use game_common::prelude::*;
+use keind::prelude::*;
+use keind_time::GameEngineTime;
+use keind_time::ManualClock;
+
+use crate::map_instance::MapInstance;
+use crate::network;
+
+mod player;
+mod remote;
+mod time;
+
+/// An instance of an empty map reading time from `clock`. Uses an in
+/// memory database and a server on an unused port. Returns the receiver
+/// of the game events of the map.
+async fn map_instance(
+    clock: &ManualClock,
+) -> anyhow::Result<(MapInstance, flume::Receiver<GameEvent>)> {
+    let network_server = Arc::new(network::Server::bind("127.0.0.1:0").await?);
+    let db =
+        redb::Database::builder().create_with_backend(redb::backends::InMemoryBackend::new())?;
+    let (game_events, game_events_receiver) = flume::unbounded();
+    let map = MapData {
+        name: "test".to_string(),
+        size: IVec2::new(1000, 1000),
+        ..Default::default()
+    };
+    let mut map_instance = MapInstance::new(map, network_server, Arc::new(db), game_events)?;
+    map_instance.engine_time = GameEngineTime::default().with_clock(Arc::new(clock.clone()));
+    Ok((map_instance, game_events_receiver))
+}
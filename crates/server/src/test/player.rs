@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use db::PlayerRecord;
+use db::PlayerStats;
+
+use super::*;
+
+#[tokio::test]
+async fn should_join_and_leave_without_diagnostics() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    let record = PlayerRecord {
+        id: "player".to_string(),
+        current_health: 100,
+        ..Default::default()
+    };
+    map_instance
+        .add_player("socket".to_string(), &record, &PlayerStats::default(), None)
+        .await?;
+    let entity_id = map_instance.player_engines["player"].entity_id;
+    clock.advance(0.1);
+    map_instance.tick().await?;
+    assert!(
+        map_instance
+            .engine
+            .entity_by_id_untyped(&entity_id, None)
+            .is_some()
+    );
+
+    map_instance.remove_player("player").await?;
+    clock.advance(0.1);
+    map_instance.tick().await?;
+    assert!(
+        map_instance
+            .engine
+            .entity_by_id_untyped(&entity_id, None)
+            .is_none()
+    );
+    assert_eq!(map_instance.diagnostic_counts, BTreeMap::new());
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use db::PlayerRecord;
+use db::PlayerStats;
+
+use super::*;
+use crate::game::RemoteEngineEvent;
+
+/// Add a player to the map with a synced engine, returning its entity id.
+async fn add_synced_player(
+    map_instance: &mut MapInstance,
+    player_id: &str,
+) -> anyhow::Result<u128> {
+    let record = PlayerRecord {
+        id: player_id.to_string(),
+        current_health: 100,
+        ..Default::default()
+    };
+    map_instance
+        .add_player("socket".to_string(), &record, &PlayerStats::default(), None)
+        .await?;
+    let engine_id = *map_instance.engine.id();
+    let player = map_instance.player_engines.get_mut(player_id).unwrap();
+    player.engine_id = Some(engine_id);
+    player.is_inited = true;
+    Ok(player.entity_id)
+}
+
+#[tokio::test]
+async fn should_keep_batch_when_rejecting_an_event() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    let entity_id = add_synced_player(&mut map_instance, "player").await?;
+    let engine_id = *map_instance.engine.id();
+    let remote_event = |event| RemoteEngineEvent {
+        player_id: "player".to_string(),
+        engine_id,
+        event,
+        step_index: 0,
+    };
+    // clients may not remove entities
+    map_instance
+        .pending_actions
+        .0
+        .send(remote_event(EngineEvent::RemoveEntity {
+            entity_id,
+            is_non_determinism: true,
+        }))?;
+    map_instance
+        .pending_actions
+        .0
+        .send(remote_event(EngineEvent::Input {
+            input: EntityInput {
+                move_left: true,
+                ..Default::default()
+            },
+            entity_id,
+            is_non_determinism: true,
+        }))?;
+    map_instance.tick().await?;
+
+    clock.advance(0.1);
+    map_instance.tick().await?;
+    assert!(
+        map_instance
+            .engine
+            .entity_by_id_untyped(&entity_id, None)
+            .is_some()
+    );
+    assert!(map_instance.engine.input_for_entity(&entity_id).move_left);
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_integrate_recent_events_when_dropping_late_ones() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    // step past the history window
+    for _ in 0..14 {
+        clock.advance(0.5);
+        map_instance.tick().await?;
+    }
+    let step_index = *map_instance.engine.step_index();
+    let item = |id, step_index| EngineEvent::SpawnEntity {
+        entity: RefPointer::new(
+            ItemEntity::new_item(id, IVec2::new(100, 100), 1, 1, 0, &step_index).into(),
+        ),
+        is_non_determinism: true,
+    };
+    map_instance.pending_events.0.send((0, item(1, 0)))?;
+    map_instance
+        .pending_events
+        .0
+        .send((step_index - 1, item(2, step_index - 1)))?;
+    map_instance.tick().await?;
+
+    assert!(map_instance.engine.entity_by_id_untyped(&1, None).is_none());
+    assert!(map_instance.engine.entity_by_id_untyped(&2, None).is_some());
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use super::*;
+
+#[tokio::test]
+async fn should_step_map_with_the_clock() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &0);
+
+    clock.advance(0.5);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &30);
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_single_step_a_paused_map() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    clock.advance(0.5);
+    map_instance.tick().await?;
+    map_instance.engine_time.pause();
+
+    clock.advance(10.0);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &30);
+
+    for step_index in 31..=33 {
+        map_instance.engine_time.step_once();
+        map_instance.tick().await?;
+        assert_eq!(map_instance.engine.step_index(), &step_index);
+    }
+
+    map_instance.engine_time.resume();
+    clock.advance(0.5);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &63);
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_slow_down_a_map() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    map_instance.engine_time.set_time_scale(0.25);
+    clock.advance(2.0);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &30);
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_spread_catch_up_over_ticks() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    clock.advance(2.0);
+    let max_steps = map_instance.engine_time.catch_up.max_steps_per_tick;
+    for tick in 1..=4 {
+        map_instance.tick().await?;
+        assert_eq!(map_instance.engine.step_index(), &(tick * max_steps));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_rebase_a_stalled_map() -> anyhow::Result<()> {
+    let clock = ManualClock::new(1000.0);
+    let (mut map_instance, _game_events) = map_instance(&clock).await?;
+    clock.advance(0.5);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &30);
+
+    // too far behind to fast forward
+    clock.advance(60.0);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &30);
+    assert_eq!(map_instance.engine_time.expected_step_index(), 30);
+
+    clock.advance(0.25);
+    map_instance.tick().await?;
+    assert_eq!(map_instance.engine.step_index(), &45);
+    Ok(())
+}